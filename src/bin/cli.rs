@@ -0,0 +1,586 @@
+//! Terminal frontend, playing the same rules as the web game through a
+//! numbered text menu.
+//!
+//! Every line read from standard input picks a menu entry or answers a
+//! prompt, and nothing but plain text is written out, so a scripted game can
+//! be compared against a golden file:
+//!
+//! ```sh
+//! printf '1\n7\n3\n' | cargo run --bin lunar-cli -- --seed 42
+//! ```
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use lunar::customs;
+use lunar::engine;
+use lunar::i18n::{self, Locale};
+use lunar::lending;
+use lunar::market::{self, Venue};
+use lunar::model::*;
+use lunar::tariffs;
+
+// What picking a menu entry does.
+enum Action {
+    Apply(Vec<Msg>),
+    // Read the next line as the answer and turn it into a message.
+    Prompt(&'static str, fn(&str) -> Msg),
+    Quit,
+}
+
+struct Choice {
+    label: String,
+    action: Action,
+}
+
+fn apply(label: &str, msg: Msg) -> Choice {
+    Choice {
+        label: label.to_string(),
+        action: Action::Apply(vec![msg]),
+    }
+}
+
+fn switch(label: &str, screen: Screen) -> Choice {
+    apply(label, Msg::SwitchScreen(screen))
+}
+
+fn prompt(label: &str, question: &'static str, parse: fn(&str) -> Msg) -> Choice {
+    Choice {
+        label: label.to_string(),
+        action: Action::Prompt(question, parse),
+    }
+}
+
+// The menus are only written in English, so are the notices about them.
+fn cannot_parse(what: &str, text: &str) -> Msg {
+    Msg::Notify(
+        Severity::Warning,
+        Text::notice(format!("Cannot parse {} from \"{}\"", what, text)),
+    )
+}
+
+fn ship_line(owner: &str, ship: &Ship) -> String {
+    format!(
+        "{}: {} ({}), hull {}/{}, crew {}/{}, cannons {}/{}, cargo {}/{}",
+        owner,
+        ship.name,
+        ship.class,
+        ship.hull,
+        ship.hull_capacity,
+        ship.crew,
+        ship.crew_capacity,
+        ship.cannons,
+        ship.cannons_capacity,
+        ship.cargos.total_unit(),
+        ship.cargos_capacity
+    )
+}
+
+fn enemy_lines(model: &Model) -> Vec<String> {
+    match &model.enemy {
+        Some(enemy) => vec![
+            ship_line(&format!("Enemy ({})", enemy.nationality), &enemy.ship),
+            format!("Distance: {}", enemy.distance),
+        ],
+        None => vec![String::from("There is no enemy in sight.")],
+    }
+}
+
+fn describe(model: &Model) -> (String, Vec<String>) {
+    let player = &model.player;
+    let port = model.ports.get(&model.current_port_location).unwrap();
+    let ship = ship_line("Your ship", &player.ship);
+
+    match &model.current_screen {
+        Screen::MainMenu => (String::from("Lunar"), vec![]),
+        Screen::About => (
+            String::from("About"),
+            vec![
+                String::from("Source code on Github: https://github.com/modotte/lunar"),
+                String::from("Licensed under the GPL-3.0-or-later license"),
+            ],
+        ),
+        Screen::SaveSlots | Screen::ImportSave => (
+            String::from("Saved games"),
+            vec![String::from(
+                "Saved games are only available in the browser.",
+            )],
+        ),
+        Screen::NewCharacter => (
+            String::from("New character"),
+            vec![
+                format!("Name: {}", player.name),
+                format!("Age: {}", player.age),
+                format!("Nationality: {}", player.nationality),
+                format!("Ship: {} ({})", player.ship.name, player.ship.class),
+                format!("Seed: {}", model.seed),
+            ],
+        ),
+        Screen::MainNavigation => (
+            port.name.to_string(),
+            vec![
+                port.description.to_string(),
+                format!("Date: {}", model.date),
+                format!("Coins: {}", player.coins),
+                format!("Food left: {}", player.ship.cargos.unit(CargoKind::Food)),
+            ],
+        ),
+        Screen::Profile => (
+            String::from("Profile"),
+            vec![
+                format!("Name: {}", player.name),
+                format!("Age: {}", player.age),
+                format!("Nationality: {}", player.nationality),
+                format!("Seed: {}", model.seed),
+                ship,
+            ],
+        ),
+        Screen::Dock => (String::from("Dock"), vec![port.name.to_string()]),
+        Screen::DockTavern => (String::from("Tavern"), vec![]),
+        Screen::TavernHireCrew => (
+            String::from("Hire crew"),
+            vec![
+                ship,
+                format!("Cost to hire all: {}", player.ship.cost_to_hire()),
+            ],
+        ),
+        Screen::TavernMoneylender => {
+            let mut lines = vec![
+                format!("Coins: {}", player.coins),
+                format!("Net worth: {}", lending::net_worth(model)),
+            ];
+            match &player.loan {
+                Some(loan) => lines.push(format!(
+                    "Owed to the moneylender at {}: {}, due by {}",
+                    loan.lender, loan.owed, loan.due
+                )),
+                None => lines.push(format!(
+                    "Lends up to {} coins at {} per mille a day, for {} days",
+                    lending::credit_limit(model),
+                    lending::DAILY_INTEREST_PERMILLE,
+                    lending::LOAN_DAYS
+                )),
+            }
+            (String::from("Moneylender"), lines)
+        }
+        Screen::DockMarket | Screen::DockBlackMarket => {
+            let venue = match model.current_screen {
+                Screen::DockBlackMarket => Venue::BlackMarket,
+                _ => Venue::Market,
+            };
+            let mut lines = vec![format!("Coins: {}", player.coins), ship];
+            if venue == Venue::Market {
+                let levies = tariffs::levies(port, player);
+                lines.push(format!(
+                    "Relations with {}: {:?}. Docking fee {}, import tariff {}%, export tariff {}%",
+                    port.nationality,
+                    tariffs::relation(&player.nationality, &port.nationality),
+                    levies.docking_fee,
+                    levies.import_percent,
+                    levies.export_percent
+                ));
+            }
+            for kind in traded_at(model, venue) {
+                let quote = market::quote_at(model, model.current_port_location, kind, venue);
+                lines.push(format!(
+                    "{}: buy {}, sell {}, available {}, in your cargo space {}",
+                    kind,
+                    quote.buy,
+                    quote.sell,
+                    port.cargos.unit(kind),
+                    player.ship.cargos.unit(kind)
+                ));
+            }
+            let title = match venue {
+                Venue::Market => "Market",
+                Venue::BlackMarket => "Black market",
+            };
+            (String::from(title), lines)
+        }
+        Screen::DockShipyard => (
+            String::from("Shipyard"),
+            vec![
+                format!("Coins: {}", player.coins),
+                ship,
+                format!("Cost to repair: {}", player.ship.cost_to_repair()),
+            ],
+        ),
+        Screen::Skirmish | Screen::SkirmishChase => {
+            let mut lines = vec![ship];
+            lines.extend(enemy_lines(model));
+            (String::from("Skirmish"), lines)
+        }
+        Screen::SkirmishBattle => {
+            let mut lines = vec![ship];
+            lines.extend(enemy_lines(model));
+            (String::from("Battle!"), lines)
+        }
+        Screen::SkirmishLoot => {
+            let mut lines = vec![ship];
+            if let Some(enemy) = &model.enemy {
+                for cargo in enemy.ship.cargos.iter().filter(|c| c.unit > 0) {
+                    lines.push(format!("Enemy {}: {}", cargo.kind, cargo.unit));
+                }
+            }
+            (String::from("Loot"), lines)
+        }
+        Screen::GameLost(reason) => (
+            String::from("Game lost"),
+            vec![String::from(match reason {
+                GameLostReason::ShipSunk => {
+                    "Your ship and your crew sunk into the deep abyss of the ocean. RIP"
+                }
+                GameLostReason::AllCrewDied => {
+                    "All your crew died and your fate is left to your enemy. RIP"
+                }
+                GameLostReason::FoodMutiny => {
+                    "You ran out of food to feed your crew, and they took the ship. RIP"
+                }
+                GameLostReason::Bankruptcy => {
+                    "You could not pay your debts, and the courts took everything. RIP"
+                }
+            })],
+        ),
+    }
+}
+
+// Goods traded on the regular market are the ones that aren't contraband here,
+// and the black market takes the rest.
+fn traded_at(model: &Model, venue: Venue) -> Vec<CargoKind> {
+    let port = model.ports.get(&model.current_port_location).unwrap();
+    CARGO_KINDS
+        .iter()
+        .filter(|&&kind| {
+            customs::is_contraband(&CONTENT, port, kind) == (venue == Venue::BlackMarket)
+        })
+        .copied()
+        .collect()
+}
+
+type TradeMsg = fn(PortLocation, CargoKind, i32) -> Msg;
+
+fn trade_choices(model: &Model, venue: Venue) -> Vec<Choice> {
+    let l = model.current_port_location;
+    let (buy, sell): (TradeMsg, TradeMsg) = match venue {
+        Venue::Market => (Msg::BuyCargo, Msg::SellCargo),
+        Venue::BlackMarket => (Msg::BuyContraband, Msg::SellContraband),
+    };
+
+    let mut choices = Vec::new();
+    for kind in traded_at(model, venue) {
+        let most = market::max_buy(model, l, kind, venue);
+        let held = model.player.ship.cargos.unit(kind);
+        let cost = market::buy_cost(model, l, kind, venue, most);
+        let proceeds = market::sell_proceeds(model, l, kind, venue, held);
+        choices.push(apply(&format!("Buy 1 {}", kind), buy(l, kind, 1)));
+        choices.push(apply(
+            &format!(
+                "Buy max: {} {} for {} coins",
+                most,
+                kind,
+                cost + tariffs::export_duty(model, l, venue, cost)
+            ),
+            buy(l, kind, most),
+        ));
+        choices.push(apply(&format!("Sell 1 {}", kind), sell(l, kind, 1)));
+        choices.push(apply(
+            &format!(
+                "Sell all: {} {} for {} coins",
+                held,
+                kind,
+                proceeds - tariffs::import_duty(model, l, venue, proceeds)
+            ),
+            sell(l, kind, held),
+        ));
+    }
+    choices
+}
+
+fn choices(model: &Model, seed: Option<u64>) -> Vec<Choice> {
+    if model.pending_confirmation.is_some() {
+        return vec![
+            apply("Confirm", Msg::Confirm),
+            apply("Cancel", Msg::CancelConfirmation),
+        ];
+    }
+
+    let coins = model.player.coins;
+    let l = model.current_port_location;
+    match &model.current_screen {
+        Screen::MainMenu => {
+            let mut new_game = vec![Msg::ResetModel];
+            new_game.extend(seed.map(Msg::SetSeed));
+            new_game.push(Msg::SwitchScreen(Screen::NewCharacter));
+            vec![
+                Choice {
+                    label: String::from("New game"),
+                    action: Action::Apply(new_game),
+                },
+                switch("About", Screen::About),
+            ]
+        }
+        Screen::About | Screen::SaveSlots | Screen::ImportSave => {
+            vec![switch("Back", Screen::MainMenu)]
+        }
+        Screen::NewCharacter => vec![
+            prompt("Set name", "Name", |text| {
+                Msg::SetPlayerName(text.to_string())
+            }),
+            prompt("Set age", "Age", |text| match text.parse::<i8>() {
+                Ok(age) => Msg::SetPlayerAge(age),
+                Err(_) => cannot_parse("an age", text),
+            }),
+            prompt(
+                "Set nationality",
+                "Nationality",
+                |text| match Nationality::from_str(text) {
+                    Ok(nationality) => Msg::SetPlayerNationality(nationality),
+                    Err(_) => cannot_parse("a nationality", text),
+                },
+            ),
+            prompt("Set ship name", "Ship name", |text| {
+                Msg::SetShipName(text.to_string())
+            }),
+            prompt(
+                "Set ship class",
+                "Ship class",
+                |text| match ShipClass::from_str(text) {
+                    Ok(class) => Msg::SetShipClass(class),
+                    Err(_) => cannot_parse("a ship class", text),
+                },
+            ),
+            prompt("Set seed", "Seed", |text| match text.parse::<u64>() {
+                Ok(seed) => Msg::SetSeed(seed),
+                Err(_) => cannot_parse("a seed", text),
+            }),
+            switch("Set sail", Screen::MainNavigation),
+            switch("Back", Screen::MainMenu),
+        ],
+        Screen::MainNavigation => {
+            let mut choices = vec![
+                switch("Profile", Screen::Profile),
+                switch("Dock", Screen::Dock),
+                switch("Skirmish", Screen::Skirmish),
+            ];
+            for &location in PORT_LOCATIONS.iter().filter(|&&p| p != l) {
+                choices.push(apply(
+                    &format!("Sail to {}", location),
+                    Msg::SwitchPlayerLocation(location),
+                ));
+            }
+            choices.push(switch("Main menu", Screen::MainMenu));
+            choices
+        }
+        Screen::Profile => vec![switch("Back", Screen::MainNavigation)],
+        Screen::Dock => {
+            let mut choices = vec![
+                switch("Tavern", Screen::DockTavern),
+                switch("Market", Screen::DockMarket),
+            ];
+            if model.ports.get(&l).unwrap().black_market {
+                choices.push(switch("Black market", Screen::DockBlackMarket));
+            }
+            choices.push(switch("Shipyard", Screen::DockShipyard));
+            choices.push(switch("Back", Screen::MainNavigation));
+            choices
+        }
+        Screen::DockTavern => vec![
+            switch("Hire crew", Screen::TavernHireCrew),
+            switch("Moneylender", Screen::TavernMoneylender),
+            switch("Back", Screen::Dock),
+        ],
+        Screen::TavernHireCrew => vec![
+            apply("Hire until full", Msg::HireCrew(coins)),
+            switch("Back", Screen::DockTavern),
+        ],
+        Screen::TavernMoneylender => {
+            let mut choices = match &model.player.loan {
+                Some(loan) => vec![
+                    prompt("Repay", "Coins", |text| match text.parse::<i32>() {
+                        Ok(amount) => Msg::RepayLoan(amount),
+                        Err(_) => cannot_parse("an amount", text),
+                    }),
+                    apply(
+                        &format!("Repay all: {} coins", loan.owed),
+                        Msg::RepayLoan(loan.owed),
+                    ),
+                ],
+                None => {
+                    let limit = lending::credit_limit(model);
+                    vec![
+                        prompt("Borrow", "Coins", |text| match text.parse::<i32>() {
+                            Ok(amount) => Msg::TakeLoan(amount),
+                            Err(_) => cannot_parse("an amount", text),
+                        }),
+                        apply(
+                            &format!("Borrow the most: {} coins", limit),
+                            Msg::TakeLoan(limit),
+                        ),
+                    ]
+                }
+            };
+            choices.push(switch("Back", Screen::DockTavern));
+            choices
+        }
+        Screen::DockMarket => {
+            let mut choices = trade_choices(model, Venue::Market);
+            choices.push(switch("Back", Screen::Dock));
+            choices
+        }
+        Screen::DockBlackMarket => {
+            let mut choices = trade_choices(model, Venue::BlackMarket);
+            choices.push(switch("Back", Screen::Dock));
+            choices
+        }
+        Screen::DockShipyard => {
+            let mut choices = vec![apply("Repair all", Msg::RepairShip(coins))];
+            for &class in SHIP_CLASSES.iter() {
+                let price = CONTENT.ships.get(&class).unwrap().price;
+                choices.push(apply(
+                    &format!("Buy a {} for {} coins", class, price),
+                    Msg::BuyAndReplaceShip(class),
+                ));
+            }
+            choices.push(switch("Back", Screen::Dock));
+            choices
+        }
+        Screen::Skirmish => vec![
+            switch("Chase", Screen::SkirmishChase),
+            switch("Abort", Screen::MainNavigation),
+        ],
+        Screen::SkirmishChase => vec![
+            apply("Close", Msg::SkirmishChaseClose),
+            apply("Distant", Msg::SkirmishChaseDistant),
+            apply("Broadside", Msg::SkirmishChaseBroadside),
+        ],
+        Screen::SkirmishBattle => match &model.enemy {
+            Some(enemy) if enemy.ship.crew < MINIMUM_SHIP_CREW.into() => vec![
+                switch("Loot enemy", Screen::SkirmishLoot),
+                switch("Continue", Screen::MainNavigation),
+            ],
+            Some(_) => vec![
+                apply("Sword attack", Msg::SkirmishBattleSwingSword),
+                apply("Shoot falconet volleys", Msg::SkirmishBattleShootFalconet),
+            ],
+            None => vec![switch("Back", Screen::MainNavigation)],
+        },
+        Screen::SkirmishLoot => {
+            let mut choices = Vec::new();
+            if let Some(enemy) = &model.enemy {
+                for cargo in enemy.ship.cargos.iter().filter(|c| c.unit > 0) {
+                    choices.push(apply(
+                        &format!("Take 1 {}", cargo.kind),
+                        Msg::TakeEnemyCargo(cargo.kind),
+                    ));
+                }
+            }
+            choices.push(switch("Continue", Screen::MainNavigation));
+            choices
+        }
+        Screen::GameLost(_) => vec![switch("Back to main menu", Screen::MainMenu)],
+    }
+}
+
+fn render(out: &mut impl Write, model: &Model, choices: &[Choice]) -> io::Result<()> {
+    let (title, lines) = describe(model);
+    writeln!(out, "== {} ==", title)?;
+    for line in lines {
+        writeln!(out, "{}", line)?;
+    }
+    if let Some(confirmation) = &model.pending_confirmation {
+        writeln!(
+            out,
+            "{}",
+            i18n::render(Locale::English, &confirmation.question)
+        )?;
+    }
+
+    writeln!(out)?;
+    for (i, choice) in choices.iter().enumerate() {
+        writeln!(out, "{}) {}", i + 1, choice.label)?;
+    }
+    writeln!(out, "0) Quit")?;
+    write!(out, "> ")?;
+    out.flush()
+}
+
+fn apply_and_report(out: &mut impl Write, model: &mut Model, msg: Msg) -> io::Result<()> {
+    let _ = engine::apply(model, msg);
+    for entry in model.toasts() {
+        writeln!(
+            out,
+            "[{:?}] {}",
+            entry.severity,
+            i18n::render(Locale::English, &entry.text)
+        )?;
+    }
+    Ok(())
+}
+
+fn seed_from_args() -> Option<u64> {
+    let args = env::args().collect::<Vec<String>>();
+    let i = args.iter().position(|a| a == "--seed")?;
+    match args.get(i + 1).map(|s| s.parse::<u64>()) {
+        Some(Ok(seed)) => Some(seed),
+        _ => {
+            eprintln!("--seed needs a positive whole number");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let seed = seed_from_args();
+    let mut model = Model::default();
+    if let Some(seed) = seed {
+        model.reseed(seed);
+    }
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock().lines();
+    let mut out = io::stdout().lock();
+
+    loop {
+        let choices = choices(&model, seed);
+        render(&mut out, &model, &choices)?;
+
+        let line = match input.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        writeln!(out)?;
+
+        let action = match line.trim().parse::<usize>() {
+            Ok(0) => &Action::Quit,
+            Ok(n) if n <= choices.len() => &choices[n - 1].action,
+            _ => {
+                writeln!(out, "Unknown choice: {}", line.trim())?;
+                continue;
+            }
+        };
+
+        match action {
+            Action::Apply(msgs) => {
+                for msg in msgs {
+                    apply_and_report(&mut out, &mut model, msg.to_owned())?;
+                }
+            }
+            Action::Prompt(question, parse) => {
+                write!(out, "{}: ", question)?;
+                out.flush()?;
+                let answer = match input.next() {
+                    Some(answer) => answer?,
+                    None => break,
+                };
+                writeln!(out)?;
+                apply_and_report(&mut out, &mut model, parse(answer.trim()))?;
+            }
+            Action::Quit => break,
+        }
+        writeln!(out)?;
+    }
+
+    writeln!(out)?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! Plays many headless games with bot strategies and prints how they went,
+//! to tune the game numbers with data instead of guesses:
+//!
+//! ```sh
+//! cargo run --release --bin lunar-simulate -- --games 1000 --strategy trader
+//! ```
+
+use std::env;
+use std::process;
+
+use lunar::simulation::{simulate, STRATEGIES};
+
+const USAGE: &str =
+    "Usage: lunar-simulate [--games N] [--actions N] [--seed N] [--strategy trader|pirate|random]";
+
+struct Options {
+    games: usize,
+    max_actions: u32,
+    seed: u64,
+    strategy: Option<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        games: 1000,
+        max_actions: 2000,
+        seed: 0,
+        strategy: None,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value", arg))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("{} needs a positive whole number", arg))
+        };
+
+        match arg.as_str() {
+            "--games" => options.games = number()? as usize,
+            "--actions" => options.max_actions = number()? as u32,
+            "--seed" => options.seed = number()?,
+            "--strategy" => options.strategy = Some(value),
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        process::exit(2);
+    });
+
+    let strategies = STRATEGIES
+        .iter()
+        .filter(|(name, _)| match &options.strategy {
+            Some(strategy) => strategy == name,
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    if strategies.is_empty() {
+        eprintln!("Unknown strategy\n{}", USAGE);
+        process::exit(2);
+    }
+
+    for (name, new_strategy) in strategies {
+        let report = simulate(
+            name,
+            *new_strategy,
+            options.games,
+            options.seed,
+            options.max_actions,
+        );
+        println!("{}", report);
+    }
+}
@@ -0,0 +1,277 @@
+//! Game content loaded from the data files under `assets/data`, so ships and
+//! ports can be rebalanced without touching the rules.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use serde::Deserialize;
+
+use crate::market;
+use crate::model::*;
+
+const CARGOS_JSON: &str = include_str!("../assets/data/cargos.json");
+const SHIPS_JSON: &str = include_str!("../assets/data/ships.json");
+const PORTS_JSON: &str = include_str!("../assets/data/ports.json");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentError {
+    pub file: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ContentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.file, self.reason)
+    }
+}
+
+fn content_error(file: &str, reason: String) -> ContentError {
+    ContentError {
+        file: file.to_string(),
+        reason,
+    }
+}
+
+// A new ship of this class, fully crewed, repaired and armed.
+#[derive(Deserialize)]
+struct ShipData {
+    class: ShipClass,
+    price: i32,
+    crew: i32,
+    hull: i16,
+    cannons: i16,
+    cargos_capacity: i32,
+    // Food that comes with the ship when bought.
+    food: i32,
+}
+
+#[derive(Deserialize)]
+struct CargoInfoData {
+    kind: CargoKind,
+    #[serde(flatten)]
+    info: CargoInfo,
+}
+
+#[derive(Deserialize)]
+struct CargoData {
+    price: i32,
+    unit: i32,
+}
+
+#[derive(Deserialize)]
+struct PortData {
+    location: PortLocation,
+    name: String,
+    description: String,
+    population: PortPopulation,
+    nationality: Nationality,
+    black_market: bool,
+    cargos: BTreeMap<CargoKind, CargoData>,
+}
+
+fn is_range(range: (i32, i32), minimum: i32) -> bool {
+    minimum <= range.0 && range.0 <= range.1
+}
+
+fn cargo_info_of(file: &str, data: &CargoInfoData) -> Result<(), ContentError> {
+    let invalid = |reason: &str| content_error(file, format!("{} {}", data.kind, reason));
+    let info = &data.info;
+
+    if info.base_price <= 0 {
+        return Err(invalid("must have a positive base price"));
+    }
+    if info.target_units <= 0 {
+        return Err(invalid("must have a positive target stock"));
+    }
+    if !is_range(info.loot_units, 0) {
+        return Err(invalid("has a range that is empty or goes below zero"));
+    }
+    if info
+        .produced_by
+        .iter()
+        .any(|n| info.demanded_by.contains(n))
+    {
+        return Err(invalid("cannot be both produced and demanded by a nation"));
+    }
+
+    Ok(())
+}
+
+fn ship_of(file: &str, data: ShipData) -> Result<Ship, ContentError> {
+    let invalid = |reason: &str| content_error(file, format!("{} {}", data.class, reason));
+
+    if data.price <= 0 {
+        return Err(invalid("must have a positive price"));
+    }
+    if data.crew < MINIMUM_SHIP_CREW.into() || data.hull < MINIMUM_SHIP_HULL.into() {
+        return Err(invalid("cannot sail with so little crew or hull"));
+    }
+    if data.cannons < MINIMUM_SHIP_CANNONS.into() {
+        return Err(invalid(&format!(
+            "needs at least {} cannons to fire a volley",
+            MINIMUM_SHIP_CANNONS
+        )));
+    }
+    if data.cargos_capacity <= 0 {
+        return Err(invalid("cannot have no cargo space"));
+    }
+    if !(0..=data.cargos_capacity).contains(&data.food) {
+        return Err(invalid("carries more food than its cargo space"));
+    }
+
+    Ok(Ship {
+        class: data.class,
+        crew: data.crew,
+        crew_capacity: data.crew,
+        hull: data.hull,
+        hull_capacity: data.hull,
+        cannons: data.cannons,
+        cannons_capacity: data.cannons,
+        cargos_capacity: data.cargos_capacity,
+        price: data.price,
+        cargos: Cargos::from_iter([Cargo {
+            unit: data.food,
+            kind: CargoKind::Food,
+            ..Default::default()
+        }]),
+        ..Default::default()
+    })
+}
+
+fn port_of(file: &str, data: PortData) -> Result<Port, ContentError> {
+    let invalid = |reason: &str| content_error(file, format!("{} {}", data.location, reason));
+
+    if data.name.trim().is_empty() || data.description.trim().is_empty() {
+        return Err(invalid("must have a name and a description"));
+    }
+
+    if data.cargos.values().any(|c| c.price <= 0 || c.unit < 0) {
+        return Err(invalid("must have positive prices and no negative stock"));
+    }
+
+    Ok(Port {
+        name: data.name,
+        description: data.description,
+        population: data.population,
+        nationality: data.nationality,
+        black_market: data.black_market,
+        cargos: data
+            .cargos
+            .iter()
+            .map(|(kind, c)| Cargo {
+                price: c.price,
+                unit: c.unit,
+                kind: *kind,
+            })
+            .collect(),
+        history: BTreeMap::new(),
+    })
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Content {
+    pub cargos: BTreeMap<CargoKind, CargoInfo>,
+    pub ships: HashMap<ShipClass, Ship>,
+    pub ports: Ports,
+}
+
+impl Content {
+    // The content set shipped with the game.
+    pub fn embedded() -> Self {
+        Self::from_json(CARGOS_JSON, SHIPS_JSON, PORTS_JSON).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn from_json(
+        cargos_json: &str,
+        ships_json: &str,
+        ports_json: &str,
+    ) -> Result<Self, ContentError> {
+        let cargo_data = serde_json::from_str::<Vec<CargoInfoData>>(cargos_json)
+            .map_err(|e| content_error("cargos.json", e.to_string()))?;
+        let ship_data = serde_json::from_str::<Vec<ShipData>>(ships_json)
+            .map_err(|e| content_error("ships.json", e.to_string()))?;
+        let port_data = serde_json::from_str::<Vec<PortData>>(ports_json)
+            .map_err(|e| content_error("ports.json", e.to_string()))?;
+
+        let mut cargos = BTreeMap::new();
+        for data in cargo_data {
+            cargo_info_of("cargos.json", &data)?;
+            if cargos.insert(data.kind, data.info).is_some() {
+                return Err(content_error(
+                    "cargos.json",
+                    format!("{} is listed twice", data.kind),
+                ));
+            }
+        }
+        if let Some(kind) = CARGO_KINDS.iter().find(|k| !cargos.contains_key(k)) {
+            return Err(content_error("cargos.json", format!("{} is missing", kind)));
+        }
+
+        let mut ships = HashMap::new();
+        for data in ship_data {
+            let class = data.class;
+            if ships.insert(class, ship_of("ships.json", data)?).is_some() {
+                return Err(content_error(
+                    "ships.json",
+                    format!("{} is listed twice", class),
+                ));
+            }
+        }
+        if let Some(class) = SHIP_CLASSES.iter().find(|c| !ships.contains_key(c)) {
+            return Err(content_error("ships.json", format!("{} is missing", class)));
+        }
+
+        let mut ports = HashMap::new();
+        for data in port_data {
+            let location = data.location;
+            if ports
+                .insert(location, port_of("ports.json", data)?)
+                .is_some()
+            {
+                return Err(content_error(
+                    "ports.json",
+                    format!("{} is listed twice", location),
+                ));
+            }
+        }
+        if let Some(location) = PORT_LOCATIONS.iter().find(|l| !ports.contains_key(l)) {
+            return Err(content_error(
+                "ports.json",
+                format!("{} is missing", location),
+            ));
+        }
+
+        let mut content = Self {
+            cargos,
+            ships,
+            ports,
+        };
+        content.stock_unlisted_goods();
+        Ok(content)
+    }
+
+    // Goods a port doesn't list in `ports.json` start at the stock it aims
+    // for, priced by whether the port produces or demands them.
+    fn stock_unlisted_goods(&mut self) {
+        let mut unlisted = Vec::new();
+        for (location, port) in self.ports.iter() {
+            for &kind in CARGO_KINDS.iter() {
+                if port.cargos.iter().all(|c| c.kind != kind) {
+                    let cargo = Cargo {
+                        price: market::listing_price(self, port, kind),
+                        unit: market::target_units(self, port, kind),
+                        kind,
+                    };
+                    unlisted.push((*location, cargo));
+                }
+            }
+        }
+
+        for (location, cargo) in unlisted {
+            let port = self.ports.get_mut(&location).unwrap();
+            port.cargos = port.cargos.iter().copied().chain([cargo]).collect();
+        }
+    }
+}
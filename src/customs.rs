@@ -0,0 +1,79 @@
+//! Contraband and the customs officers looking for it. A port's regular market
+//! refuses goods its nation bans, only a black market deals in them, and
+//! sailing into port with them on board risks an inspection.
+
+use rand::Rng;
+
+use crate::content::Content;
+use crate::i18n::Named;
+use crate::model::*;
+
+// How much more the black market charges and pays than the regular market,
+// in percent.
+pub const BLACK_MARKET_PREMIUM: i32 = 50;
+// Fine for every seized unit, in percent of its base price.
+pub const FINE_PERCENT: i32 = 50;
+
+pub fn is_contraband(content: &Content, port: &Port, kind: CargoKind) -> bool {
+    content.cargos[&kind].banned_by.contains(&port.nationality)
+}
+
+// Odds in percent that officers search a ship coming into `port`. Busy ports
+// have more of them, and ports with a black market look the other way.
+pub fn inspection_chance(port: &Port) -> u32 {
+    let chance = match port.population {
+        PortPopulation::Small => 10,
+        PortPopulation::Medium => 20,
+        PortPopulation::Large => 30,
+        PortPopulation::Huge => 40,
+    };
+    if port.black_market {
+        chance / 2
+    } else {
+        chance
+    }
+}
+
+// Search the player's ship on arrival. Contraband found is seized and fined,
+// as far as the player's coins go.
+pub fn inspect(content: &Content, model: &mut Model) {
+    let location = model.current_port_location;
+    let port = &model.ports[&location];
+    let found: Vec<Cargo> = model
+        .player
+        .ship
+        .cargos
+        .iter()
+        .filter(|c| c.unit > 0 && is_contraband(content, port, c.kind))
+        .copied()
+        .collect();
+    if found.is_empty() || model.rng.gen_range(0..100) >= inspection_chance(port) {
+        return;
+    }
+
+    let value: i32 = found
+        .iter()
+        .map(|c| c.unit * content.cargos[&c.kind].base_price)
+        .sum();
+    let fine = (value * FINE_PERCENT / 100).min(model.player.coins);
+    for cargo in found.iter() {
+        model.player.ship.cargos.get_mut(cargo.kind).unit = 0;
+    }
+    model.player.coins -= fine;
+
+    let seized = found
+        .iter()
+        .map(|c| {
+            Text::new("log.units")
+                .arg("units", c.unit)
+                .text("cargo", c.kind.text())
+        })
+        .collect();
+    model.log(
+        Severity::Danger,
+        Text::new("log.customs_seized")
+            .text("port", location.text())
+            .texts("goods", seized)
+            .arg("fine", fine),
+    );
+}
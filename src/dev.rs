@@ -0,0 +1,43 @@
+//! Developer commands for poking at a game while testing it. Only built with
+//! the `dev-tools` feature, so release builds never carry them.
+
+use std::ops::AddAssign;
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{self, GameError};
+use crate::model::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DevCommand {
+    SetCoins(i32),
+    SpawnEnemy(ShipClass, Nationality),
+    Teleport(PortLocation),
+    AdvanceDate(u16),
+    ForceLoss(GameLostReason),
+}
+
+pub fn apply(state: &mut Model, command: DevCommand) -> Result<(), GameError> {
+    state.log(
+        Severity::Info,
+        Text::new("log.dev_command").arg("command", format!("{:?}", command)),
+    );
+    match command {
+        // Coins are never negative, see `invariants::check`.
+        DevCommand::SetCoins(coins) => state.player.coins = coins.max(0),
+        DevCommand::SpawnEnemy(class, nationality) => {
+            engine::spawn_enemy(state, class, nationality)
+        }
+        // Skips the voyage, so nothing restocks or spoils on the way.
+        DevCommand::Teleport(location) => {
+            state.current_port_location = location;
+            state.enemy = None;
+            state.current_screen = Screen::MainNavigation;
+        }
+        DevCommand::AdvanceDate(days) => state.date.add_assign(Duration::days(days.into())),
+        DevCommand::ForceLoss(reason) => engine::lose_game(state, reason),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Developer console shown under every screen when built with the `dev-tools`
+//! feature, e.g. `trunk serve --features dev-tools`.
+
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::view::onclick_styled_btn;
+use lunar::dev::DevCommand;
+use lunar::model::*;
+use lunar::transfer::{export_json, import};
+use lunar::web::load;
+
+const LOSS_REASONS: [GameLostReason; 4] = [
+    GameLostReason::ShipSunk,
+    GameLostReason::AllCrewDied,
+    GameLostReason::FoodMutiny,
+    GameLostReason::Bankruptcy,
+];
+
+// The console itself is not translated, only the prefix of what it logs.
+fn dev_text(text: impl std::fmt::Display) -> Text {
+    Text::new("log.dev_command").arg("command", text)
+}
+
+fn dev_btn(dispatch: &Dispatch<Model>, command: DevCommand, btn_text: &str) -> Html {
+    onclick_styled_btn(
+        dispatch.apply_callback(move |_| Msg::Dev(command.to_owned())),
+        btn_text,
+    )
+}
+
+// A select over `names`, keeping the index of the chosen one in `selected`.
+fn index_select(names: Vec<String>, selected: &UseStateHandle<usize>) -> Html {
+    let onchange = {
+        let selected = selected.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            selected.set(select.selected_index().max(0) as usize)
+        })
+    };
+
+    html! {
+        <div class="select is-small">
+            <select {onchange}>
+                { for names.iter().enumerate().map(|(i, name)| html! {
+                    <option selected={i == **selected}>{name}</option>
+                }) }
+            </select>
+        </div>
+    }
+}
+
+fn number_input(value: &UseStateHandle<String>) -> Html {
+    let oninput = {
+        let value = value.clone();
+        Callback::from(move |e: InputEvent| {
+            value.set(e.target_unchecked_into::<HtmlInputElement>().value())
+        })
+    };
+
+    html! { <input class="input is-small" type="number" value={(**value).clone()} {oninput} /> }
+}
+
+// Replace the current game with `text` and start a new journal for it.
+fn load_game(dispatch: &Dispatch<Model>, text: &str) {
+    match import(text) {
+        Ok(model) => {
+            if let Err(violation) = load(model) {
+                dispatch.apply(Msg::Notify(Severity::Danger, violation.text()));
+                return;
+            }
+            dispatch.apply(Msg::Notify(Severity::Info, dev_text("loaded a game")));
+        }
+        Err(e) => dispatch.apply(Msg::Notify(Severity::Danger, e.text())),
+    }
+}
+
+#[function_component]
+pub fn DevConsole() -> Html {
+    let (model, dispatch) = use_store::<Model>();
+    let coins = use_state(|| model.player.coins.to_string());
+    let days = use_state(|| String::from("30"));
+    let class = use_state(|| 0);
+    let nationality = use_state(|| 0);
+    let location = use_state(|| 0);
+    let json = use_state(String::new);
+
+    let set_coins = {
+        let dispatch = dispatch.clone();
+        let coins = coins.clone();
+        Callback::from(move |_| match coins.parse::<i32>() {
+            Ok(c) => dispatch.apply(Msg::Dev(DevCommand::SetCoins(c))),
+            Err(e) => dispatch.apply(Msg::Notify(Severity::Warning, dev_text(e))),
+        })
+    };
+    let advance_date = {
+        let dispatch = dispatch.clone();
+        let days = days.clone();
+        Callback::from(move |_| match days.parse::<u16>() {
+            Ok(d) => dispatch.apply(Msg::Dev(DevCommand::AdvanceDate(d))),
+            Err(e) => dispatch.apply(Msg::Notify(Severity::Warning, dev_text(e))),
+        })
+    };
+    let dump = {
+        let model = model.clone();
+        let json = json.clone();
+        Callback::from(move |_| json.set(export_json(&model)))
+    };
+    let load = {
+        let dispatch = dispatch.clone();
+        let json = json.clone();
+        Callback::from(move |_| load_game(&dispatch, json.as_str()))
+    };
+    let oninput = {
+        let json = json.clone();
+        Callback::from(move |e: InputEvent| {
+            json.set(e.target_unchecked_into::<HtmlTextAreaElement>().value())
+        })
+    };
+
+    let spawn = DevCommand::SpawnEnemy(SHIP_CLASSES[*class], NATIONALITIES[*nationality].clone());
+    let teleport = DevCommand::Teleport(PORT_LOCATIONS[*location]);
+
+    html! {
+        <div class="box dev-console">
+            <h2>{"Developer console"}</h2>
+            <hr/>
+
+            <label>{"Coins"}</label>
+            { number_input(&coins) }
+            { onclick_styled_btn(set_coins, "Set coins") }
+
+            <br/>
+            <label>{"Enemy"}</label>
+            <br/>
+            { index_select(SHIP_CLASSES.iter().map(|c| c.to_string()).collect(), &class) }
+            { index_select(NATIONALITIES.iter().map(|n| n.to_string()).collect(), &nationality) }
+            { dev_btn(&dispatch, spawn, "Spawn enemy") }
+
+            <br/>
+            <label>{"Port"}</label>
+            <br/>
+            { index_select(PORT_LOCATIONS.iter().map(|l| l.to_string()).collect(), &location) }
+            { dev_btn(&dispatch, teleport, "Teleport") }
+
+            <br/>
+            <label>{"Days"}</label>
+            { number_input(&days) }
+            { onclick_styled_btn(advance_date, "Advance date") }
+
+            <br/>
+            <label>{"Lose the game"}</label>
+            <br/>
+            { for LOSS_REASONS.iter().map(|r| dev_btn(&dispatch, DevCommand::ForceLoss(*r), &format!("{:?}", r))) }
+
+            <br/>
+            <label>{"Model as JSON"}</label>
+            <textarea class="textarea is-small" value={(*json).clone()} {oninput} />
+            { onclick_styled_btn(dump, "Dump") }
+            { onclick_styled_btn(load, "Load") }
+        </div>
+    }
+}
@@ -0,0 +1,831 @@
+//! Game rules, free of any browser or yew dependency.
+//!
+//! Every frontend drives the game through [`apply`]. What happened is written
+//! to the captain's log in the model, including a returned [`GameError`].
+
+use std::{fmt, ops::AddAssign};
+
+use chrono::Duration;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::customs;
+use crate::i18n::{self, Locale, Named};
+use crate::invariants;
+use crate::lending;
+use crate::market::{self, Venue};
+use crate::model::*;
+use crate::tariffs;
+use crate::transitions;
+
+/// Why an action was refused. When one is returned, nothing but the log and
+/// toasts changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    InvalidAge(i8),
+    AlreadyInPort(PortLocation),
+    InsufficientFunds { needed: i32, available: i32 },
+    CargoHoldFull { room: i32 },
+    PortOutOfStock { kind: CargoKind, available: i32 },
+    NothingToSell { kind: CargoKind, available: i32 },
+    InvalidQuantity(i32),
+    Contraband(CargoKind),
+    NotContraband(CargoKind),
+    NoBlackMarket(PortLocation),
+    InvalidAmount(i32),
+    LoanOutstanding { owed: i32 },
+    LoanTooLarge { limit: i32 },
+    NoLoan,
+    NothingToLoot(CargoKind),
+    NotInCombat,
+    EnemyNotDefeated,
+    ScreenUnreachable { from: Screen, to: Screen },
+    NothingToConfirm,
+}
+
+impl GameError {
+    // What the player is told, translated when shown.
+    pub fn text(&self) -> Text {
+        match self {
+            Self::InvalidAge(age) => Text::new("error.invalid_age")
+                .arg("age", age)
+                .arg("min", MINIMUM_PLAYER_AGE)
+                .arg("max", MAXIMUM_PLAYER_AGE),
+            Self::AlreadyInPort(l) => Text::new("error.already_in_port").text("port", l.text()),
+            Self::InsufficientFunds { needed, available } => Text::new("error.insufficient_funds")
+                .arg("needed", needed)
+                .arg("available", available),
+            Self::CargoHoldFull { room: 0 } => Text::new("error.cargo_hold_full"),
+            Self::CargoHoldFull { room } => Text::new("error.cargo_hold_room").arg("room", room),
+            Self::PortOutOfStock { kind, available: 0 } => {
+                Text::new("error.port_sold_out").text("cargo", kind.text())
+            }
+            Self::PortOutOfStock { kind, available } => Text::new("error.port_out_of_stock")
+                .arg("available", available)
+                .text("cargo", kind.text()),
+            Self::NothingToSell { kind, available: 0 } => {
+                Text::new("error.nothing_to_sell").text("cargo", kind.text())
+            }
+            Self::NothingToSell { kind, available } => Text::new("error.not_enough_to_sell")
+                .arg("available", available)
+                .text("cargo", kind.text()),
+            Self::InvalidQuantity(units) => Text::new("error.invalid_quantity").arg("units", units),
+            Self::Contraband(kind) => Text::new("error.contraband").text("cargo", kind.text()),
+            Self::NotContraband(kind) => {
+                Text::new("error.not_contraband").text("cargo", kind.text())
+            }
+            Self::NoBlackMarket(l) => Text::new("error.no_black_market").text("port", l.text()),
+            Self::InvalidAmount(coins) => Text::new("error.invalid_amount").arg("coins", coins),
+            Self::LoanOutstanding { owed } => Text::new("error.loan_outstanding").arg("owed", owed),
+            Self::LoanTooLarge { limit } => Text::new("error.loan_too_large").arg("limit", limit),
+            Self::NoLoan => Text::new("error.no_loan"),
+            Self::NothingToLoot(kind) => {
+                Text::new("error.nothing_to_loot").text("cargo", kind.text())
+            }
+            Self::NotInCombat => Text::new("error.not_in_combat"),
+            Self::EnemyNotDefeated => Text::new("error.enemy_not_defeated"),
+            Self::ScreenUnreachable { from, to } => Text::new("error.screen_unreachable")
+                .arg("from", format!("{:?}", from))
+                .arg("to", format!("{:?}", to)),
+            Self::NothingToConfirm => Text::new("error.nothing_to_confirm"),
+        }
+    }
+}
+
+// In English, as in the terminal frontend.
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", i18n::render(Locale::English, &self.text()))
+    }
+}
+
+fn ensure_funds(p: &Player, needed: i32) -> Result<(), GameError> {
+    if p.coins < needed {
+        return Err(GameError::InsufficientFunds {
+            needed,
+            available: p.coins,
+        });
+    }
+    Ok(())
+}
+
+fn ensure_cargo_space(p: &Player, units: i32) -> Result<(), GameError> {
+    let room = p.ship.cargos_capacity - p.ship.cargos.total_unit();
+    if room < units {
+        return Err(GameError::CargoHoldFull { room: room.max(0) });
+    }
+    Ok(())
+}
+
+fn ensure_quantity(units: i32) -> Result<(), GameError> {
+    if units < 1 {
+        return Err(GameError::InvalidQuantity(units));
+    }
+    Ok(())
+}
+
+// A volley spends a unit of gunpowder, when there is some, to hit harder.
+fn gunpowder_bonus(ship: &mut Ship) -> i16 {
+    if ship.cargos.unit(CargoKind::Gunpowder) == 0 {
+        return 0;
+    }
+    ship.cargos.get_mut(CargoKind::Gunpowder).unit -= 1;
+    GUNPOWDER_DAMAGE
+}
+
+// A unit of medicine, when there is some, saves half of the `crew` about to
+// be lost.
+fn casualties(ship: &mut Ship, crew: i32) -> i32 {
+    if crew < 2 || ship.cargos.unit(CargoKind::Medicine) == 0 {
+        return crew;
+    }
+    ship.cargos.get_mut(CargoKind::Medicine).unit -= 1;
+    crew - crew / 2
+}
+
+// Charged on every arrival, as far as the player's coins go.
+fn pay_docking_fee(state: &mut Model) {
+    let l = state.current_port_location;
+    let fee = tariffs::levies(&state.ports[&l], &state.player)
+        .docking_fee
+        .min(state.player.coins);
+    if fee > 0 {
+        state.player.coins -= fee;
+        state.log(
+            Severity::Warning,
+            Text::new("log.docking_fee")
+                .arg("fee", fee)
+                .text("port", l.text()),
+        );
+    }
+}
+
+// Contraband only changes hands on a black market, and legal goods only on
+// the regular one.
+fn ensure_venue(
+    state: &Model,
+    l: PortLocation,
+    kind: CargoKind,
+    venue: Venue,
+) -> Result<(), GameError> {
+    let port = state.ports.get(&l).unwrap();
+    let contraband = customs::is_contraband(&CONTENT, port, kind);
+    match venue {
+        Venue::Market if contraband => Err(GameError::Contraband(kind)),
+        Venue::BlackMarket if !port.black_market => Err(GameError::NoBlackMarket(l)),
+        Venue::BlackMarket if !contraband => Err(GameError::NotContraband(kind)),
+        _ => Ok(()),
+    }
+}
+
+// The duty and the venue are only mentioned when there is something to say
+// about them.
+fn trade_text(
+    key: &str,
+    units: i32,
+    kind: CargoKind,
+    coins: i32,
+    duty: Option<Text>,
+    venue: Venue,
+) -> Text {
+    let venue = match venue {
+        Venue::Market => None,
+        Venue::BlackMarket => Some(Text::new("log.on_the_black_market")),
+    };
+    Text::new(key)
+        .arg("units", units)
+        .text("cargo", kind.text())
+        .arg("coins", coins)
+        .optional("duty", duty)
+        .optional("venue", venue)
+}
+
+fn duty_text(key: &str, duty: i32) -> Option<Text> {
+    match duty {
+        0 => None,
+        duty => Some(Text::new(key).arg("duty", duty)),
+    }
+}
+
+// Every unit moves the price of the next one, and the whole lot is refused if
+// any part of it cannot go through.
+fn buy(
+    state: &mut Model,
+    l: PortLocation,
+    kind: CargoKind,
+    venue: Venue,
+    units: i32,
+) -> Result<(), GameError> {
+    ensure_quantity(units)?;
+    ensure_venue(state, l, kind, venue)?;
+    let available = state.ports.get(&l).unwrap().cargos.unit(kind);
+    if available < units {
+        return Err(GameError::PortOutOfStock { kind, available });
+    }
+    let price = market::buy_cost(state, l, kind, venue, units);
+    let duty = tariffs::export_duty(state, l, venue, price);
+    ensure_funds(&state.player, price + duty)?;
+    ensure_cargo_space(&state.player, units)?;
+
+    state.player.coins -= price + duty;
+    // We don't need to pattern match the get_mut(l)
+    // because of enum as hashmap key usage
+    let port = state.ports.get_mut(&l).unwrap();
+    port.cargos.get_mut(kind).unit -= units;
+    market::reprice(&CONTENT, l, port);
+    market::record(port, state.date);
+    state.player.ship.cargos.get_mut(kind).unit += units;
+    state.log(
+        Severity::Info,
+        trade_text(
+            "log.bought",
+            units,
+            kind,
+            price + duty,
+            duty_text("log.including_export_duty", duty),
+            venue,
+        ),
+    );
+    Ok(())
+}
+
+fn sell(
+    state: &mut Model,
+    l: PortLocation,
+    kind: CargoKind,
+    venue: Venue,
+    units: i32,
+) -> Result<(), GameError> {
+    ensure_quantity(units)?;
+    ensure_venue(state, l, kind, venue)?;
+    let available = state.player.ship.cargos.unit(kind);
+    if available < units {
+        return Err(GameError::NothingToSell { kind, available });
+    }
+
+    let price = market::sell_proceeds(state, l, kind, venue, units);
+    let duty = tariffs::import_duty(state, l, venue, price);
+    let port = state.ports.get_mut(&l).unwrap();
+    state.player.coins += price - duty;
+    port.cargos.get_mut(kind).unit += units;
+    market::reprice(&CONTENT, l, port);
+    market::record(port, state.date);
+    state.player.ship.cargos.get_mut(kind).unit -= units;
+    state.log(
+        Severity::Info,
+        trade_text(
+            "log.sold",
+            units,
+            kind,
+            price - duty,
+            duty_text("log.after_import_duty", duty),
+            venue,
+        ),
+    );
+    Ok(())
+}
+
+fn take_loan(state: &mut Model, amount: i32) -> Result<(), GameError> {
+    if amount < 1 {
+        return Err(GameError::InvalidAmount(amount));
+    }
+    if let Some(loan) = &state.player.loan {
+        return Err(GameError::LoanOutstanding { owed: loan.owed });
+    }
+    let limit = lending::credit_limit(state);
+    if amount > limit {
+        return Err(GameError::LoanTooLarge { limit });
+    }
+
+    let loan = lending::new_loan(state, amount);
+    let due = loan.due;
+    state.player.coins += amount;
+    state.player.loan = Some(loan);
+    state.log(
+        Severity::Success,
+        Text::new("log.borrowed")
+            .arg("coins", amount)
+            .arg("due", due),
+    );
+    Ok(())
+}
+
+// Paying more than is owed only pays off the loan.
+fn repay_loan(state: &mut Model, amount: i32) -> Result<(), GameError> {
+    let owed = match &state.player.loan {
+        Some(loan) => loan.owed,
+        None => return Err(GameError::NoLoan),
+    };
+    if amount < 1 {
+        return Err(GameError::InvalidAmount(amount));
+    }
+    let paid = amount.min(owed);
+    ensure_funds(&state.player, paid)?;
+
+    state.player.coins -= paid;
+    if paid == owed {
+        state.player.loan = None;
+        state.log(
+            Severity::Success,
+            Text::new("log.paid_off").arg("coins", paid),
+        );
+    } else {
+        state.player.loan.as_mut().unwrap().owed -= paid;
+        state.log(
+            Severity::Success,
+            Text::new("log.repaid")
+                .arg("coins", paid)
+                .arg("owed", owed - paid),
+        );
+    }
+    Ok(())
+}
+
+fn choice_of<T: Clone>(rng: &mut impl Rng, sequence: &[T], default: &T) -> T {
+    sequence.choose(rng).unwrap_or(default).to_owned()
+}
+
+// Send player to the game lost screen and start over with a fresh game.
+pub(crate) fn lose_game(state: &mut Model, reason: GameLostReason) {
+    state.log(
+        Severity::Danger,
+        Text::new(&format!("log.lost.{:?}", reason)),
+    );
+    state.current_screen = Screen::GameLost(reason);
+    let m = Model::default();
+    state.date = m.date;
+    state.current_port_location = m.current_port_location;
+    state.player = m.player;
+    state.ports = m.ports;
+    state.enemy = None;
+}
+
+// Bring an enemy ship with a random name and loot into a new skirmish.
+pub(crate) fn spawn_enemy(state: &mut Model, class: ShipClass, nationality: Nationality) {
+    let rng = &mut state.rng;
+    let mut new_enemy = Enemy {
+        ship: CONTENT.ships.get(&class).unwrap().clone(),
+        nationality,
+        ..Default::default()
+    };
+
+    new_enemy.ship.name = choice_of(rng, &ENEMY_SHIP_NAMES, &ENEMY_SHIP_NAMES[0]).to_string();
+    // Loot that doesn't fit in the enemy's hold is left behind.
+    for kind in CARGO_KINDS.iter() {
+        let (min, max) = CONTENT.cargos[kind].loot_units;
+        let room = new_enemy.ship.cargos_capacity - new_enemy.ship.cargos.total_unit()
+            + new_enemy.ship.cargos.unit(*kind);
+        new_enemy.ship.cargos.get_mut(*kind).unit = rng.gen_range(min..=max).min(room);
+    }
+
+    state.log(
+        Severity::Info,
+        Text::new("log.enemy_spotted")
+            .text("nationality", new_enemy.nationality.text())
+            .text("class", new_enemy.ship.class.text())
+            .text(
+                "name",
+                Text::new(&format!("enemy_ship.{}", new_enemy.ship.name)),
+            ),
+    );
+    state.enemy = Some(new_enemy);
+    state.current_screen = Screen::Skirmish;
+}
+
+fn replace_ship(model: &mut Model, sc: &ShipClass) -> Result<(), GameError> {
+    let l = model.current_port_location;
+    // We gift player free food in new ship.
+    let mut s = CONTENT.ships.get(sc).unwrap().clone();
+    s.name = model.player.ship.name.to_string();
+
+    ensure_funds(&model.player, s.price)?;
+
+    // The old cargo is sold off at what the port pays for it.
+    let cargo_value: i32 = model
+        .player
+        .ship
+        .cargos
+        .iter()
+        .map(|c| c.unit * market::quote(model, l, c.kind).sell)
+        .sum();
+    model.player.coins += cargo_value;
+    model.player.coins -= s.price;
+    model.player.ship = s;
+    model.log(
+        Severity::Success,
+        Text::new("log.replaced_ship")
+            .text("class", sc.text())
+            .arg("coins", cargo_value),
+    );
+    Ok(())
+}
+
+pub fn apply(state: &mut Model, msg: Msg) -> Result<(), GameError> {
+    // Toasts only show what this action added to the log.
+    state.toasts_from = state.log.len();
+
+    #[cfg(debug_assertions)]
+    let applied = msg.clone();
+
+    let result = apply_msg(state, msg);
+    if let Err(error) = &result {
+        state.log(Severity::Warning, error.text());
+    }
+
+    #[cfg(debug_assertions)]
+    if let Err(violation) = invariants::check(state) {
+        panic!(
+            "{} broke an invariant: {}",
+            serde_json::to_string(&applied).unwrap(),
+            violation
+        );
+    }
+    result
+}
+
+fn apply_msg(state: &mut Model, msg: Msg) -> Result<(), GameError> {
+    match msg {
+        Msg::ResetModel => {
+            *state = Model::default();
+            Ok(())
+        }
+        Msg::SetPlayerName(name) => {
+            state.player.name = name;
+            Ok(())
+        }
+        Msg::SetPlayerAge(age) => {
+            if !(MINIMUM_PLAYER_AGE..=MAXIMUM_PLAYER_AGE).contains(&age) {
+                return Err(GameError::InvalidAge(age));
+            }
+
+            state.player.age = age;
+            Ok(())
+        }
+        Msg::SetPlayerNationality(nationality) => {
+            state.player.nationality = nationality;
+            Ok(())
+        }
+        Msg::SetShipName(name) => {
+            state.player.ship.name = name;
+            Ok(())
+        }
+        Msg::SetShipClass(sc) => {
+            let mut ship_choice = CONTENT.ships.get(&sc).unwrap().clone();
+            ship_choice.name = state.player.ship.name.to_string();
+
+            state.player.ship = ship_choice;
+            Ok(())
+        }
+        Msg::SetSeed(seed) => {
+            state.reseed(seed);
+            Ok(())
+        }
+        Msg::SwitchScreen(s) => {
+            transitions::check(state, &s)?;
+            match s {
+                Screen::Skirmish => {
+                    // Pick from the ordered class list rather than the ships map,
+                    // since a HashMap iteration order would break reproducibility.
+                    let class = choice_of(&mut state.rng, &SHIP_CLASSES, &ShipClass::default());
+                    let nationality = choice_of(&mut state.rng, &NATIONALITIES, &NATIONALITIES[0]);
+                    spawn_enemy(state, class, nationality);
+                }
+                Screen::SkirmishChase | Screen::SkirmishBattle | Screen::SkirmishLoot => {
+                    state.current_screen = s
+                }
+                // Leaving the skirmish leaves the enemy behind.
+                _ => {
+                    state.enemy = None;
+                    state.current_screen = s
+                }
+            };
+            Ok(())
+        }
+
+        Msg::SwitchPlayerLocation(l) => {
+            if state.current_port_location == l {
+                return Err(GameError::AlreadyInPort(l));
+            }
+
+            let days: i64 = state.rng.gen_range(1..=9);
+            state.date.add_assign(Duration::days(days));
+            state.current_port_location = l;
+            state.log(
+                Severity::Info,
+                Text::new("log.arrived")
+                    .text("port", l.text())
+                    .arg("days", days),
+            );
+
+            market::drift(&CONTENT, &mut state.ports, &mut state.rng, days);
+            for port in state.ports.values_mut() {
+                market::record(port, state.date);
+            }
+            pay_docking_fee(state);
+            customs::inspect(&CONTENT, state);
+
+            if state.player.ship.cargos.unit(CargoKind::Food) < MINIMUM_PLAYER_FOOD.into() {
+                lose_game(state, GameLostReason::FoodMutiny);
+            } else {
+                for (kind, info) in CONTENT.cargos.iter() {
+                    if info.perishable && state.player.ship.cargos.unit(*kind) > 0 {
+                        state.player.ship.cargos.get_mut(*kind).unit -= state.rng.gen_range(0..=1);
+                    }
+                }
+                lending::settle(state);
+            }
+            Ok(())
+        }
+
+        Msg::BuyCargo(l, kind, units) => buy(state, l, kind, Venue::Market, units),
+        Msg::SellCargo(l, kind, units) => sell(state, l, kind, Venue::Market, units),
+        Msg::BuyContraband(l, kind, units) => buy(state, l, kind, Venue::BlackMarket, units),
+        Msg::SellContraband(l, kind, units) => sell(state, l, kind, Venue::BlackMarket, units),
+        Msg::SkirmishChaseClose => {
+            if state.enemy.is_none() {
+                return Err(GameError::NotInCombat);
+            }
+
+            if state.player.ship.hull < MINIMUM_SHIP_HULL.into() {
+                lose_game(state, GameLostReason::ShipSunk);
+                return Ok(());
+            }
+
+            let enemy = state.enemy.as_mut().unwrap();
+
+            if enemy.ship.hull < MINIMUM_SHIP_HULL.into() {
+                state.enemy = None;
+                state.current_screen = Screen::MainNavigation;
+            } else {
+                let rng = &mut state.rng;
+                let player_cannons = state.player.ship.cannons;
+                let enemy_cannons = enemy.ship.cannons;
+                match enemy.distance {
+                    EnemyDistance::Escape => {
+                        state
+                            .player
+                            .ship
+                            .take_damage(rng.gen_range(0..=enemy_cannons - 3));
+
+                        enemy.ship.take_damage(
+                            rng.gen_range(0..=player_cannons - 3)
+                                + gunpowder_bonus(&mut state.player.ship),
+                        );
+                        enemy.distance = EnemyDistance::Far
+                    }
+                    EnemyDistance::Far => {
+                        state
+                            .player
+                            .ship
+                            .take_damage(rng.gen_range(0..=enemy_cannons - 3));
+
+                        enemy.ship.take_damage(
+                            rng.gen_range(0..=player_cannons - 3)
+                                + gunpowder_bonus(&mut state.player.ship),
+                        );
+                        enemy.distance = EnemyDistance::Close
+                    }
+                    EnemyDistance::Close => {
+                        state
+                            .player
+                            .ship
+                            .take_damage(rng.gen_range(1..=enemy_cannons - 2));
+
+                        enemy.ship.take_damage(
+                            rng.gen_range(1..=player_cannons - 2)
+                                + gunpowder_bonus(&mut state.player.ship),
+                        );
+                        enemy.distance = EnemyDistance::Board
+                    }
+                    EnemyDistance::Board => state.current_screen = Screen::SkirmishBattle,
+                }
+            }
+            Ok(())
+        }
+        Msg::SkirmishChaseDistant => {
+            if state.enemy.is_none() {
+                return Err(GameError::NotInCombat);
+            }
+
+            if state.player.ship.hull < MINIMUM_SHIP_HULL.into() {
+                lose_game(state, GameLostReason::ShipSunk);
+                return Ok(());
+            }
+
+            let enemy = state.enemy.as_mut().unwrap();
+
+            if enemy.ship.hull < MINIMUM_SHIP_HULL.into() {
+                state.enemy = None;
+                state.current_screen = Screen::MainNavigation;
+            } else {
+                let rng = &mut state.rng;
+                let player_cannons = state.player.ship.cannons;
+                let enemy_cannons = enemy.ship.cannons;
+                match enemy.distance {
+                    EnemyDistance::Escape => {
+                        state.enemy = None;
+                        state.current_screen = Screen::MainNavigation
+                    }
+                    EnemyDistance::Far => {
+                        state
+                            .player
+                            .ship
+                            .take_damage(rng.gen_range(0..=enemy_cannons - 3));
+
+                        enemy.ship.take_damage(
+                            rng.gen_range(0..=player_cannons - 3)
+                                + gunpowder_bonus(&mut state.player.ship),
+                        );
+                        enemy.distance = EnemyDistance::Escape
+                    }
+                    EnemyDistance::Close => {
+                        state
+                            .player
+                            .ship
+                            .take_damage(rng.gen_range(0..=enemy_cannons - 2));
+
+                        enemy.ship.take_damage(
+                            rng.gen_range(0..=player_cannons - 2)
+                                + gunpowder_bonus(&mut state.player.ship),
+                        );
+                        enemy.distance = EnemyDistance::Far
+                    }
+                    EnemyDistance::Board => state.current_screen = Screen::SkirmishBattle,
+                }
+            }
+            Ok(())
+        }
+        Msg::SkirmishChaseBroadside => {
+            if state.enemy.is_none() {
+                return Err(GameError::NotInCombat);
+            }
+
+            if state.player.ship.hull < MINIMUM_SHIP_HULL.into() {
+                lose_game(state, GameLostReason::ShipSunk);
+                return Ok(());
+            }
+
+            let enemy = state.enemy.as_mut().unwrap();
+
+            if enemy.ship.hull < MINIMUM_SHIP_HULL.into() {
+                state.enemy = None;
+                state.current_screen = Screen::MainNavigation;
+            } else {
+                let rng = &mut state.rng;
+                let player_cannons = state.player.ship.cannons;
+                let enemy_cannons = enemy.ship.cannons;
+                match enemy.distance {
+                    EnemyDistance::Escape => {
+                        state
+                            .player
+                            .ship
+                            .take_damage(rng.gen_range(0..=enemy_cannons - 4));
+
+                        enemy.ship.take_damage(
+                            rng.gen_range(0..=player_cannons - 4)
+                                + gunpowder_bonus(&mut state.player.ship),
+                        );
+                    }
+                    EnemyDistance::Far => {
+                        state
+                            .player
+                            .ship
+                            .take_damage(rng.gen_range(0..=enemy_cannons - 3));
+                        enemy.ship.take_damage(
+                            rng.gen_range(0..=player_cannons - 3)
+                                + gunpowder_bonus(&mut state.player.ship),
+                        );
+                    }
+                    EnemyDistance::Close => {
+                        state
+                            .player
+                            .ship
+                            .take_damage(rng.gen_range(1..=enemy_cannons - 2));
+
+                        enemy.ship.take_damage(
+                            rng.gen_range(1..=player_cannons - 2)
+                                + gunpowder_bonus(&mut state.player.ship),
+                        );
+                    }
+                    EnemyDistance::Board => (),
+                }
+            }
+            Ok(())
+        }
+        Msg::SkirmishBattleSwingSword => {
+            if state.enemy.is_none() {
+                return Err(GameError::NotInCombat);
+            }
+
+            if state.player.ship.crew < MINIMUM_SHIP_CREW.into() {
+                lose_game(state, GameLostReason::AllCrewDied);
+                return Ok(());
+            }
+
+            let enemy = state.enemy.as_mut().unwrap();
+
+            let rng = &mut state.rng;
+            let lost = casualties(&mut state.player.ship, rng.gen_range(1..=2));
+            state.player.ship.lose_crew(lost);
+            enemy.ship.lose_crew(rng.gen_range(1..=2));
+            Ok(())
+        }
+        Msg::SkirmishBattleShootFalconet => {
+            if state.enemy.is_none() {
+                return Err(GameError::NotInCombat);
+            }
+
+            if state.player.ship.crew < MINIMUM_SHIP_CREW.into() {
+                lose_game(state, GameLostReason::AllCrewDied);
+                return Ok(());
+            }
+
+            let enemy = state.enemy.as_mut().unwrap();
+
+            let lost = casualties(&mut state.player.ship, state.rng.gen_range(1..=4));
+            state.player.ship.lose_crew(lost);
+
+            enemy.ship.lose_crew(state.rng.gen_range(1..=5));
+            Ok(())
+        }
+
+        Msg::RepairShip(coins) => {
+            // Never spend more than the player actually has.
+            let available = coins.min(state.player.coins);
+            if available < state.player.ship.cost_to_repair() {
+                return Err(GameError::InsufficientFunds {
+                    needed: state.player.ship.cost_to_repair(),
+                    available,
+                });
+            }
+
+            let cost = state.player.ship.cost_to_repair();
+            state.player.coins -= cost;
+            state.player.ship.hull = state.player.ship.hull_capacity;
+            state.log(
+                Severity::Success,
+                Text::new("log.repaired").arg("coins", cost),
+            );
+            Ok(())
+        }
+
+        Msg::TakeLoan(amount) => take_loan(state, amount),
+        Msg::RepayLoan(amount) => repay_loan(state, amount),
+        Msg::TakeEnemyCargo(c) => {
+            let enemy_cargos = match &mut state.enemy {
+                Some(enemy) => &mut enemy.ship.cargos,
+                None => return Err(GameError::NotInCombat),
+            };
+
+            if enemy_cargos.unit(c) == 0 {
+                return Err(GameError::NothingToLoot(c));
+            }
+            ensure_cargo_space(&state.player, 1)?;
+
+            enemy_cargos.get_mut(c).unit -= 1;
+            state.player.ship.cargos.get_mut(c).unit += 1;
+            state.log(
+                Severity::Success,
+                Text::new("log.looted").text("cargo", c.text()),
+            );
+            Ok(())
+        }
+        Msg::BuyAndReplaceShip(sc) => replace_ship(state, &sc),
+        Msg::HireCrew(coins) => {
+            let available = coins.min(state.player.coins);
+            if available < state.player.ship.cost_to_hire() {
+                return Err(GameError::InsufficientFunds {
+                    needed: state.player.ship.cost_to_hire(),
+                    available,
+                });
+            }
+
+            let cost = state.player.ship.cost_to_hire();
+            state.player.coins -= cost;
+            state.player.ship.crew = state.player.ship.crew_capacity;
+            state.log(Severity::Success, Text::new("log.hired").arg("coins", cost));
+            Ok(())
+        }
+        Msg::Notify(severity, text) => {
+            state.log(severity, text);
+            Ok(())
+        }
+        Msg::DismissToasts => {
+            state.toasts_from = state.log.len();
+            Ok(())
+        }
+        Msg::AskConfirmation(question, msg) => {
+            state.pending_confirmation = Some(Confirmation { question, msg });
+            Ok(())
+        }
+        Msg::Confirm => match state.pending_confirmation.take() {
+            Some(confirmation) => apply_msg(state, *confirmation.msg),
+            None => Err(GameError::NothingToConfirm),
+        },
+        Msg::CancelConfirmation => {
+            state.pending_confirmation = None;
+            Ok(())
+        }
+        #[cfg(feature = "dev-tools")]
+        Msg::Dev(command) => crate::dev::apply(state, command),
+    }
+}
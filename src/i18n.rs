@@ -0,0 +1,190 @@
+//! Player-facing text, looked up by key in the message catalogs under
+//! `assets/locales`.
+//!
+//! A key missing from the chosen locale falls back to English, and then to
+//! the key itself. English port names and descriptions come from
+//! `assets/data/ports.json` rather than the catalog.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::model::*;
+use crate::tariffs::Relation;
+
+const EN_JSON: &str = include_str!("../assets/locales/en.json");
+const FR_JSON: &str = include_str!("../assets/locales/fr.json");
+
+#[derive(Default, Debug, EnumIter, Copy, Clone, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+impl Locale {
+    // Locales tried in turn for a key, most preferred first.
+    pub fn fallbacks(&self) -> Vec<Locale> {
+        match self {
+            Self::English => vec![Self::English],
+            _ => vec![*self, Self::English],
+        }
+    }
+}
+
+pub type Catalog = BTreeMap<String, String>;
+
+fn catalog_of(locale: Locale) -> Catalog {
+    let json = match locale {
+        Locale::English => EN_JSON,
+        Locale::French => FR_JSON,
+    };
+    let mut catalog = serde_json::from_str::<Catalog>(json)
+        .unwrap_or_else(|e| panic!("{:?} catalog: {}", locale, e));
+
+    if locale == Locale::English {
+        for (location, port) in CONTENT.ports.iter() {
+            catalog.insert(location.key(), port.name.to_string());
+            catalog.insert(
+                format!("port.{:?}.description", location),
+                port.description.to_string(),
+            );
+        }
+    }
+    catalog
+}
+
+lazy_static! {
+    pub static ref LOCALES: Vec<Locale> = Locale::iter().collect();
+    static ref CATALOGS: HashMap<Locale, Catalog> =
+        LOCALES.iter().map(|l| (*l, catalog_of(*l))).collect();
+}
+
+pub fn tr(locale: Locale, key: &str) -> String {
+    locale
+        .fallbacks()
+        .iter()
+        .find_map(|l| CATALOGS[l].get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+// Fills in every `{name}` placeholder of the text with its argument.
+pub fn tr_with(locale: Locale, key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    args.iter().fold(tr(locale, key), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
+
+// Text from the log or an error, with its arguments filled in through
+// `tr_with` after translating those that are text of their own.
+pub fn render(locale: Locale, text: &Text) -> String {
+    let args = text
+        .args
+        .iter()
+        .map(|(name, arg)| {
+            let value = match arg {
+                Arg::Plain(value) => value.to_string(),
+                Arg::Text(text) => render(locale, text),
+                Arg::Texts(texts) => texts
+                    .iter()
+                    .map(|text| render(locale, text))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            };
+            (name.as_str(), value)
+        })
+        .collect::<Vec<(&str, String)>>();
+    let args = args
+        .iter()
+        .map(|(name, value)| (*name, value as &dyn fmt::Display))
+        .collect::<Vec<(&str, &dyn fmt::Display)>>();
+    tr_with(locale, &text.key, &args)
+}
+
+// Game enums shown to the player, translated under `<PREFIX>.<Variant>`.
+pub trait Named: fmt::Debug {
+    const PREFIX: &'static str;
+
+    fn key(&self) -> String {
+        format!("{}.{:?}", Self::PREFIX, self)
+    }
+
+    fn name(&self, locale: Locale) -> String {
+        tr(locale, &self.key())
+    }
+
+    fn text(&self) -> Text {
+        Text::new(&self.key())
+    }
+}
+
+impl Named for Locale {
+    const PREFIX: &'static str = "locale";
+}
+
+impl Named for Nationality {
+    const PREFIX: &'static str = "nationality";
+}
+
+impl Named for ShipClass {
+    const PREFIX: &'static str = "ship_class";
+}
+
+impl Named for CargoKind {
+    const PREFIX: &'static str = "cargo";
+}
+
+impl Named for Relation {
+    const PREFIX: &'static str = "relation";
+}
+
+impl Named for PortLocation {
+    const PREFIX: &'static str = "port";
+
+    fn key(&self) -> String {
+        format!("port.{:?}.name", self)
+    }
+}
+
+// Enemy ships carry one of the stock names, translated for display. Any other
+// name was chosen by the player and is shown as it is.
+pub fn ship_name(locale: Locale, name: &str) -> String {
+    if ENEMY_SHIP_NAMES.contains(&name) {
+        tr(locale, &format!("enemy_ship.{}", name))
+    } else {
+        name.to_string()
+    }
+}
+
+// Every key a shipped locale has to translate.
+pub fn required_keys() -> Vec<String> {
+    let mut keys = CATALOGS[&Locale::English]
+        .keys()
+        .cloned()
+        .collect::<Vec<String>>();
+    keys.extend(LOCALES.iter().map(|l| l.key()));
+    keys.extend(NATIONALITIES.iter().map(|n| n.key()));
+    keys.extend(SHIP_CLASSES.iter().map(|c| c.key()));
+    keys.extend(CARGO_KINDS.iter().map(|k| k.key()));
+    keys.extend(Relation::iter().map(|r| r.key()));
+    keys.extend(GameLostReason::iter().map(|r| format!("game_lost.{:?}", r)));
+    keys.extend(ENEMY_SHIP_NAMES.iter().map(|n| format!("enemy_ship.{}", n)));
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+// Keys the locale leaves to its fallbacks.
+pub fn missing_keys(locale: Locale) -> Vec<String> {
+    required_keys()
+        .into_iter()
+        .filter(|k| !CATALOGS[&locale].contains_key(k))
+        .collect()
+}
@@ -0,0 +1,129 @@
+//! Rules a `Model` has to follow between any two actions.
+//!
+//! [`check`] runs after every action in debug builds, in the property tests
+//! under `tests/`, and on every imported save.
+
+use std::fmt;
+
+use crate::model::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation(pub String);
+
+impl Violation {
+    // Shown to a player whose save breaks a rule, with the rule in English.
+    pub fn text(&self) -> Text {
+        Text::new("saves.invalid").arg("reason", &self.0)
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn violation<T>(reason: String) -> Result<T, Violation> {
+    Err(Violation(reason))
+}
+
+fn check_ship(owner: &str, ship: &Ship) -> Result<(), Violation> {
+    if let Some(c) = ship.cargos.iter().find(|c| c.unit < 0) {
+        return violation(format!("{} ship has {} {}", owner, c.unit, c.kind));
+    }
+    if ship.cargos.total_unit() > ship.cargos_capacity {
+        return violation(format!(
+            "{} ship carries {} cargo units, but only has space for {}",
+            owner,
+            ship.cargos.total_unit(),
+            ship.cargos_capacity
+        ));
+    }
+    if !(0..=ship.hull_capacity).contains(&ship.hull) {
+        return violation(format!(
+            "{} ship hull is {}, but must be from 0 to {}",
+            owner, ship.hull, ship.hull_capacity
+        ));
+    }
+    if !(0..=ship.crew_capacity).contains(&ship.crew) {
+        return violation(format!(
+            "{} ship crew is {}, but must be from 0 to {}",
+            owner, ship.crew, ship.crew_capacity
+        ));
+    }
+    if !(MINIMUM_SHIP_CANNONS.into()..=ship.cannons_capacity).contains(&ship.cannons) {
+        return violation(format!(
+            "{} ship has {} cannons, but must be from {} to {}",
+            owner, ship.cannons, MINIMUM_SHIP_CANNONS, ship.cannons_capacity
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn check(model: &Model) -> Result<(), Violation> {
+    if model.player.coins < 0 {
+        return violation(format!("Player coins are {}", model.player.coins));
+    }
+    check_ship("Player", &model.player.ship)?;
+    if let Some(loan) = &model.player.loan {
+        if loan.owed < 1 {
+            return violation(format!("Player owes {} coins", loan.owed));
+        }
+    }
+
+    match &model.enemy {
+        Some(enemy) if model.current_screen.is_skirmish() => check_ship("Enemy", &enemy.ship)?,
+        None if !model.current_screen.is_skirmish() => (),
+        Some(_) => {
+            return violation(format!(
+                "Enemy is still around on the {:?} screen",
+                model.current_screen
+            ))
+        }
+        None => {
+            return violation(format!(
+                "There is no enemy on the {:?} screen",
+                model.current_screen
+            ))
+        }
+    }
+
+    if let Some(l) = PORT_LOCATIONS.iter().find(|l| !model.ports.contains_key(l)) {
+        return violation(format!("Port {} is missing", l));
+    }
+    if !model.ports.contains_key(&model.current_port_location) {
+        return violation(format!(
+            "Player is docked at unknown port {}",
+            model.current_port_location
+        ));
+    }
+    for (l, port) in model.ports.iter() {
+        if let Some(kind) = CARGO_KINDS
+            .iter()
+            .find(|k| !port.cargos.iter().any(|c| c.kind == **k))
+        {
+            return violation(format!("Port {} does not stock {}", l, kind));
+        }
+        if port.cargos.iter().any(|c| c.unit < 0 || c.price < 0) {
+            return violation(format!("Port {} has negative stock or prices", l));
+        }
+        if port
+            .history
+            .values()
+            .any(|p| p.len() > MAXIMUM_PRICE_HISTORY)
+        {
+            return violation(format!("Port {} keeps too long a price history", l));
+        }
+    }
+
+    if model.log.len() > MAXIMUM_LOG_ENTRIES || model.toasts_from > model.log.len() {
+        return violation(format!(
+            "Captain's log has {} entries with toasts from {}",
+            model.log.len(),
+            model.toasts_from
+        ));
+    }
+
+    Ok(())
+}
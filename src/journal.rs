@@ -0,0 +1,176 @@
+//! Append-only record of every message the player dispatched, so a game can
+//! be exported, shared and replayed step by step.
+
+use std::{fmt, io};
+
+use chrono::NaiveDate;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::engine;
+use crate::i18n::{self, Locale};
+use crate::migrations;
+use crate::model::*;
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct JournalEntry {
+    #[serde(deserialize_with = "deserialize_msg")]
+    pub msg: Msg,
+    // State right after `msg` was applied, used to spot a diverging replay.
+    pub date: NaiveDate,
+    pub screen: Screen,
+    // Entries recorded before digests were kept have none, and are only
+    // checked on their date and screen.
+    #[serde(default)]
+    pub digest: Option<u64>,
+}
+
+fn deserialize_msg<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Msg, D::Error> {
+    let mut value = Value::deserialize(deserializer)?;
+    migrations::upgrade_msg(&mut value);
+    serde_json::from_value(value).map_err(de::Error::custom)
+}
+
+// FNV-1a, fed the game as JSON. Unlike the std hasher it is the same in every
+// build, so a journal exported from one version checks out in another.
+struct Fnv(u64);
+
+impl io::Write for Fnv {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Fingerprint of the player, the ports and the rng, which together hold
+// everything a replay has to reproduce.
+pub fn digest(model: &Model) -> u64 {
+    // Ports are walked in a fixed order, a HashMap's would change every run.
+    let ports = PORT_LOCATIONS
+        .iter()
+        .filter_map(|l| model.ports.get(l))
+        .collect::<Vec<&Port>>();
+    let mut hasher = Fnv(0xcbf2_9ce4_8422_2325);
+    serde_json::to_writer(&mut hasher, &(&model.player, ports, &model.rng)).unwrap();
+    hasher.0
+}
+
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "web", derive(yewdux::prelude::Store))]
+pub struct Journal {
+    // Seed of the model before the first entry was applied.
+    pub seed: u64,
+    // The game before the first entry was applied, when it was not a new one.
+    // Journals without it replay from a new game with `seed`.
+    #[serde(default)]
+    pub start: Option<Model>,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    // For a new game, like `Model::default` reseeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            start: None,
+            entries: Vec::new(),
+        }
+    }
+
+    // For a game picked up where it was, like an imported or a loaded one.
+    pub fn starting_from(model: &Model) -> Self {
+        Self {
+            seed: model.seed,
+            start: Some(model.to_owned()),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, msg: Msg, model: &Model) {
+        self.entries.push(JournalEntry {
+            msg,
+            date: model.date,
+            screen: model.current_screen.to_owned(),
+            digest: Some(digest(model)),
+        });
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    // Zero-based index of the first entry that did not match.
+    pub step: usize,
+    pub recorded_date: NaiveDate,
+    pub recorded_screen: Screen,
+    pub replayed_date: NaiveDate,
+    pub replayed_screen: Screen,
+    // Set when the date and screen match but the rest of the game does not.
+    pub state_differs: bool,
+}
+
+impl Divergence {
+    // What the player is told, translated when shown.
+    pub fn text(&self) -> Text {
+        if self.state_differs {
+            return Text::new("profile.journal_state_diverged")
+                .arg("step", self.step)
+                .arg("date", self.replayed_date)
+                .arg("screen", format!("{:?}", self.replayed_screen));
+        }
+        Text::new("profile.journal_diverged")
+            .arg("step", self.step)
+            .arg("recorded_date", self.recorded_date)
+            .arg("recorded_screen", format!("{:?}", self.recorded_screen))
+            .arg("replayed_date", self.replayed_date)
+            .arg("replayed_screen", format!("{:?}", self.replayed_screen))
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", i18n::render(Locale::English, &self.text()))
+    }
+}
+
+// Rebuild a game from where the journal started by applying every entry in
+// order.
+pub fn replay(journal: &Journal) -> Result<Model, Divergence> {
+    let mut model = match &journal.start {
+        Some(start) => start.to_owned(),
+        None => {
+            let mut model = Model::default();
+            model.reseed(journal.seed);
+            model
+        }
+    };
+
+    for (step, entry) in journal.entries.iter().enumerate() {
+        // A refused action was refused when recorded too, so it replays alike.
+        let _ = engine::apply(&mut model, entry.msg.to_owned());
+
+        let moved = model.date != entry.date || model.current_screen != entry.screen;
+        let state_differs = !moved && matches!(entry.digest, Some(d) if d != digest(&model));
+        if moved || state_differs {
+            return Err(Divergence {
+                step,
+                recorded_date: entry.date,
+                recorded_screen: entry.screen.to_owned(),
+                replayed_date: model.date,
+                replayed_screen: model.current_screen,
+                state_differs,
+            });
+        }
+    }
+
+    Ok(model)
+}
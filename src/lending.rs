@@ -0,0 +1,163 @@
+//! Moneylenders in the taverns, lending against what the player owns. Debts
+//! compound every day that passes at sea, and each missed due date brings
+//! worse trouble: bounty hunters, then the seizure of the ship, then
+//! bankruptcy.
+
+use chrono::Duration;
+
+use crate::engine;
+use crate::i18n::Named;
+use crate::market;
+use crate::model::*;
+
+// Most a moneylender lends, in percent of the player's net worth.
+pub const LOAN_PERCENT: i32 = 50;
+// Interest added every day, in tenths of a percent of what is owed.
+pub const DAILY_INTEREST_PERMILLE: i32 = 5;
+// Days from borrowing to the due date.
+pub const LOAN_DAYS: i64 = 60;
+// Days added to the due date after a missed one.
+pub const GRACE_DAYS: i64 = 15;
+// Added to the debt on every missed due date, in percent of what is owed.
+pub const LATE_FEE_PERCENT: i32 = 10;
+
+// Coins, plus the ship and its cargo at what the current port pays for them.
+pub fn net_worth(model: &Model) -> i32 {
+    let l = model.current_port_location;
+    let ship = &model.player.ship;
+    let cargo: i32 = ship
+        .cargos
+        .iter()
+        .map(|c| c.unit * market::quote(model, l, c.kind).sell)
+        .sum();
+    model.player.coins + ship.price + cargo
+}
+
+pub fn credit_limit(model: &Model) -> i32 {
+    net_worth(model) * LOAN_PERCENT / 100
+}
+
+// What `owed` grows to after `days`, with interest rounded up every day.
+pub fn compound(owed: i32, days: i64) -> i32 {
+    let rate = i64::from(DAILY_INTEREST_PERMILLE);
+    (0..days).fold(owed, |owed, _| {
+        let owed = i64::from(owed);
+        (owed + (owed * rate + 999) / 1000).min(i32::MAX.into()) as i32
+    })
+}
+
+pub fn new_loan(model: &Model, amount: i32) -> Loan {
+    Loan {
+        lender: model.current_port_location,
+        owed: amount,
+        due: model.date + Duration::days(LOAN_DAYS),
+        accrued_on: model.date,
+        missed: 0,
+    }
+}
+
+// Called on every arrival. Interest is added up to today and, once the due
+// date has passed, the lender's agents take what coins they find. A debt
+// still left is a missed due date.
+pub fn settle(model: &mut Model) {
+    let date = model.date;
+    let loan = match &mut model.player.loan {
+        Some(loan) => loan,
+        None => return,
+    };
+    loan.owed = compound(loan.owed, (date - loan.accrued_on).num_days());
+    loan.accrued_on = date;
+    if date <= loan.due {
+        return;
+    }
+
+    let collected = loan.owed.min(model.player.coins);
+    loan.owed -= collected;
+    model.player.coins -= collected;
+    if loan.owed == 0 {
+        model.player.loan = None;
+        model.log(
+            Severity::Warning,
+            Text::new("log.debt_collected").arg("coins", collected),
+        );
+        return;
+    }
+
+    loan.missed += 1;
+    let late_fee = (i64::from(loan.owed) * i64::from(LATE_FEE_PERCENT) + 99) / 100;
+    loan.owed = loan.owed.saturating_add(late_fee as i32);
+    loan.due = date + Duration::days(GRACE_DAYS);
+    let (missed, owed, due) = (loan.missed, loan.owed, loan.due);
+    let lender = loan.lender;
+    model.log(
+        Severity::Danger,
+        Text::new("log.missed_due_date")
+            .arg("coins", owed)
+            .arg("due", due),
+    );
+
+    match missed {
+        1 => send_bounty_hunters(model, lender),
+        2 => seize_ship(model),
+        _ => engine::lose_game(model, GameLostReason::Bankruptcy),
+    }
+}
+
+// They sail under the lender's flag and come straight at the player.
+fn send_bounty_hunters(model: &mut Model, lender: PortLocation) {
+    model.log(Severity::Danger, Text::new("log.bounty_hunters"));
+    let nationality = model.ports[&lender].nationality.clone();
+    engine::spawn_enemy(model, ShipClass::Brig, nationality);
+    if let Some(enemy) = model.enemy.as_mut() {
+        enemy.distance = EnemyDistance::Close;
+    }
+    model.current_screen = Screen::SkirmishChase;
+}
+
+// The lender sells the player's ship, leaves them the cheapest one there is,
+// and takes the difference off the debt. Cargo that fits is moved over, after
+// food for the crew to reach the next port.
+fn seize_ship(model: &mut Model) {
+    let cheapest = SHIP_CLASSES
+        .iter()
+        .map(|class| &CONTENT.ships[class])
+        .min_by_key(|ship| ship.price)
+        .unwrap();
+    let mut ship = cheapest.clone();
+    ship.name = model.player.ship.name.to_string();
+    ship.cargos = Cargos::default();
+    let food = model
+        .player
+        .ship
+        .cargos
+        .unit(CargoKind::Food)
+        .max(MINIMUM_PLAYER_FOOD.into());
+    ship.cargos.get_mut(CargoKind::Food).unit = food.min(ship.cargos_capacity);
+    for cargo in model
+        .player
+        .ship
+        .cargos
+        .iter()
+        .filter(|c| c.kind != CargoKind::Food)
+    {
+        let room = ship.cargos_capacity - ship.cargos.total_unit();
+        ship.cargos.get_mut(cargo.kind).unit = cargo.unit.min(room);
+    }
+    let credit = (model.player.ship.price - ship.price).max(0);
+    let class = model.player.ship.class;
+    model.player.ship = ship;
+
+    let loan = model.player.loan.as_mut().unwrap();
+    let refund = (credit - loan.owed).max(0);
+    loan.owed -= credit - refund;
+    model.player.coins += refund;
+    if loan.owed == 0 {
+        model.player.loan = None;
+    }
+    model.log(
+        Severity::Danger,
+        Text::new("log.ship_seized")
+            .text("class", class.text())
+            .arg("coins", credit - refund),
+    );
+}
@@ -0,0 +1,25 @@
+//! Game rules and state, shared by the web frontend (`src/main.rs`) and the
+//! terminal frontend (`src/bin/cli.rs`). The browser stores are only built
+//! with the `web` feature, see `src/web.rs`.
+
+pub mod content;
+pub mod customs;
+#[cfg(feature = "dev-tools")]
+pub mod dev;
+pub mod engine;
+pub mod i18n;
+pub mod invariants;
+pub mod journal;
+pub mod lending;
+pub mod market;
+pub mod migrations;
+pub mod model;
+pub mod routing;
+pub mod saves;
+pub mod settings;
+pub mod simulation;
+pub mod tariffs;
+pub mod transfer;
+pub mod transitions;
+#[cfg(feature = "web")]
+pub mod web;
@@ -0,0 +1,269 @@
+//! Port markets. Every port aims to hold a stock of each good that grows with
+//! its population, and its prices follow how far the stock is from that
+//! target: buying pushes a price up, dumping goods crashes it, and stocks
+//! drift back toward the target as days pass.
+
+use chrono::NaiveDate;
+use rand::Rng;
+
+use crate::content::Content;
+use crate::customs::BLACK_MARKET_PREMIUM;
+use crate::model::*;
+use crate::tariffs;
+
+// Days for a market to get halfway back to its target stock.
+pub const RECOVERY_DAYS: i64 = 10;
+
+// What a port makes of a good.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Role {
+    Produces,
+    Demands,
+    Trades,
+}
+
+pub fn role(content: &Content, port: &Port, kind: CargoKind) -> Role {
+    let info = &content.cargos[&kind];
+    let wealthy = matches!(
+        port.population,
+        PortPopulation::Large | PortPopulation::Huge
+    );
+
+    if info.produced_by.contains(&port.nationality) {
+        Role::Produces
+    } else if info.demanded_by.contains(&port.nationality) || (info.luxury && wealthy) {
+        Role::Demands
+    } else {
+        Role::Trades
+    }
+}
+
+// Producers aim for twice the usual stock, and ports that demand a good make
+// do with half of it.
+pub fn target_units(content: &Content, port: &Port, kind: CargoKind) -> i32 {
+    let units = content.cargos[&kind].target_units * port.population.market_size() / 100;
+    match role(content, port, kind) {
+        Role::Produces => units * 2,
+        Role::Demands => units / 2,
+        Role::Trades => units,
+    }
+}
+
+// Price at the target stock of a good the port doesn't list in `ports.json`.
+pub fn listing_price(content: &Content, port: &Port, kind: CargoKind) -> i32 {
+    let base = content.cargos[&kind].base_price;
+    match role(content, port, kind) {
+        Role::Produces => base * 3 / 4,
+        Role::Demands => base * 3 / 2,
+        Role::Trades => base,
+    }
+}
+
+// What the port pays at its target stock, as listed in `ports.json`.
+pub fn reference_price(content: &Content, location: PortLocation, kind: CargoKind) -> i32 {
+    content.ports[&location].cargos.get(kind).price
+}
+
+// Four times the reference price when sold out, and a quarter of it with
+// three times the target in stock.
+pub fn price_at(reference: i32, target: i32, stock: i32) -> i32 {
+    let reference = i64::from(reference);
+    let target = i64::from(target.max(1));
+    let stock = i64::from(stock.max(0));
+
+    let price = reference * 4 * target * target / ((stock + target) * (stock + target));
+    price.max(1) as i32
+}
+
+fn targets_of(content: &Content, port: &Port) -> Vec<i32> {
+    port.cargos
+        .iter()
+        .map(|c| target_units(content, port, c.kind))
+        .collect()
+}
+
+pub fn reprice(content: &Content, location: PortLocation, port: &mut Port) {
+    let targets = targets_of(content, port);
+    for (cargo, target) in port.cargos.iter_mut().zip(targets) {
+        cargo.price = price_at(
+            reference_price(content, location, cargo.kind),
+            target,
+            cargo.unit,
+        );
+    }
+}
+
+// Let `days` go by in every port. Stocks close part of the gap to their
+// target, give or take a twentieth of it, and prices follow.
+pub fn drift(content: &Content, ports: &mut Ports, rng: &mut impl Rng, days: i64) {
+    // Walk the ports and kinds in a fixed order, so the same seed moves the
+    // same markets the same way.
+    for location in PORT_LOCATIONS.iter() {
+        let port = ports.get_mut(location).unwrap();
+        let targets = targets_of(content, port);
+        for (cargo, target) in port.cargos.iter_mut().zip(targets) {
+            let gap = i64::from(target - cargo.unit);
+            let noise = target / 20;
+
+            cargo.unit += (gap * days / (days + RECOVERY_DAYS)) as i32;
+            cargo.unit = (cargo.unit + rng.gen_range(-noise..=noise)).max(0);
+        }
+        reprice(content, *location, port);
+    }
+}
+
+// Note the port's prices and stocks on `date`. A later trade on the same day
+// replaces that day's point, and only the last `MAXIMUM_PRICE_HISTORY` days
+// are kept.
+pub fn record(port: &mut Port, date: NaiveDate) {
+    for cargo in port.cargos.iter() {
+        let points = port.history.entry(cargo.kind).or_default();
+        if matches!(points.back(), Some(p) if p.date == date) {
+            points.pop_back();
+        }
+        points.push_back(PricePoint {
+            date,
+            price: cargo.price,
+            unit: cargo.unit,
+        });
+        while points.len() > MAXIMUM_PRICE_HISTORY {
+            points.pop_front();
+        }
+    }
+}
+
+// What a port charges for a good and what it pays for one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Quote {
+    pub buy: i32,
+    pub sell: i32,
+}
+
+// Gap between the buy and sell price, in percent of the market price. Busy
+// ports trade on thin margins, and foreign captains pay extra.
+pub fn spread(port: &Port, player: &Player) -> i32 {
+    let size = match port.population {
+        PortPopulation::Small => 24,
+        PortPopulation::Medium => 16,
+        PortPopulation::Large => 12,
+        PortPopulation::Huge => 8,
+    };
+    // TODO: Narrow the spread for captains with a good reputation.
+    let foreigner = if player.nationality == port.nationality {
+        0
+    } else {
+        8
+    };
+    size + foreigner
+}
+
+// Half the spread on each side of the market price, never selling for less
+// than it buys.
+pub fn quote_of(cargo: &Cargo, spread: i32) -> Quote {
+    let price = i64::from(cargo.price);
+    let spread = i64::from(spread);
+
+    let sell = (price * (200 - spread) / 200).max(1);
+    let buy = ((price * (200 + spread) + 199) / 200).max(sell + 1);
+    Quote {
+        buy: buy as i32,
+        sell: sell as i32,
+    }
+}
+
+// Where a port's goods change hands. The black market deals in contraband
+// above the market price, and with twice the spread.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Venue {
+    Market,
+    BlackMarket,
+}
+
+fn venue_quote(price: i32, spread: i32, venue: Venue) -> Quote {
+    let (price, spread) = match venue {
+        Venue::Market => (price, spread),
+        Venue::BlackMarket => (price * (100 + BLACK_MARKET_PREMIUM) / 100, spread * 2),
+    };
+    quote_of(
+        &Cargo {
+            price,
+            ..Default::default()
+        },
+        spread,
+    )
+}
+
+pub fn quote(model: &Model, location: PortLocation, kind: CargoKind) -> Quote {
+    quote_at(model, location, kind, Venue::Market)
+}
+
+pub fn quote_at(model: &Model, location: PortLocation, kind: CargoKind, venue: Venue) -> Quote {
+    let port = &model.ports[&location];
+    venue_quote(
+        port.cargos.get(kind).price,
+        spread(port, &model.player),
+        venue,
+    )
+}
+
+// Quotes for trading `units` one after another, each trade moving the stock
+// by `step` and so the price of the next unit.
+fn walk(
+    model: &Model,
+    location: PortLocation,
+    kind: CargoKind,
+    venue: Venue,
+    units: i32,
+    step: i32,
+) -> impl Iterator<Item = Quote> {
+    let port = &model.ports[&location];
+    let reference = reference_price(&CONTENT, location, kind);
+    let target = target_units(&CONTENT, port, kind);
+    let stock = port.cargos.unit(kind);
+    let spread = spread(port, &model.player);
+
+    (0..units.max(0))
+        .map(move |i| venue_quote(price_at(reference, target, stock + step * i), spread, venue))
+}
+
+// Total asked for buying `units` in one go.
+pub fn buy_cost(
+    model: &Model,
+    location: PortLocation,
+    kind: CargoKind,
+    venue: Venue,
+    units: i32,
+) -> i32 {
+    walk(model, location, kind, venue, units, -1)
+        .map(|q| q.buy)
+        .sum()
+}
+
+// Total paid for selling `units` in one go.
+pub fn sell_proceeds(
+    model: &Model,
+    location: PortLocation,
+    kind: CargoKind,
+    venue: Venue,
+    units: i32,
+) -> i32 {
+    walk(model, location, kind, venue, units, 1)
+        .map(|q| q.sell)
+        .sum()
+}
+
+// Most units the player can buy at once, limited by the port's stock, the
+// room left in the hold and the coins at hand, tariffs included.
+pub fn max_buy(model: &Model, location: PortLocation, kind: CargoKind, venue: Venue) -> i32 {
+    let ship = &model.player.ship;
+    let room = ship.cargos_capacity - ship.cargos.total_unit();
+    let limit = model.ports[&location].cargos.unit(kind).min(room);
+
+    let mut cost = 0;
+    walk(model, location, kind, venue, limit, -1)
+        .take_while(|q| {
+            cost += q.buy;
+            cost + tariffs::export_duty(model, location, venue, cost) <= model.player.coins
+        })
+        .count() as i32
+}
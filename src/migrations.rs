@@ -0,0 +1,347 @@
+//! Upgrades saved games from older `Model` shapes to the current one.
+//!
+//! Whenever a change to the model would break deserializing an existing save,
+//! bump `SCHEMA_VERSION` and append a migration to `MIGRATIONS` that turns the
+//! previous JSON shape into the new one.
+
+use std::fmt;
+
+use chrono::NaiveDate;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::model::*;
+
+pub const SCHEMA_VERSION: u32 = 10;
+
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+// Entry `i` upgrades a save from version `i` to version `i + 1`.
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [
+    v0_add_seeded_rng,
+    v1_key_cargos_by_kind,
+    v2_add_captains_log,
+    v3_enforce_invariants,
+    v4_add_price_history,
+    v5_stock_new_goods,
+    v6_add_black_markets,
+    v7_add_loans,
+    v8_translate_log,
+    v9_translate_questions,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    NotAnObject,
+    // The save was written by a newer version of the game.
+    UnknownVersion(u32),
+    Failed { from: u32, reason: String },
+    Invalid(String),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnObject => write!(f, "Save is not a JSON object"),
+            Self::UnknownVersion(v) => write!(
+                f,
+                "Save has schema version {}, but this game only knows up to {}",
+                v, SCHEMA_VERSION
+            ),
+            Self::Failed { from, reason } => write!(
+                f,
+                "Cannot upgrade save from schema version {}: {}",
+                from, reason
+            ),
+            Self::Invalid(reason) => {
+                write!(f, "Save does not match the current schema: {}", reason)
+            }
+        }
+    }
+}
+
+// Saves from before versioning was introduced have no `schema_version`.
+fn schema_version_of(value: &Value) -> Result<u32, MigrationError> {
+    match value.get("schema_version") {
+        None => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or_else(|| MigrationError::Invalid(String::from("schema_version is not a number"))),
+    }
+}
+
+pub fn migrate(mut value: Value) -> Result<Model, MigrationError> {
+    if !value.is_object() {
+        return Err(MigrationError::NotAnObject);
+    }
+
+    let version = schema_version_of(&value)?;
+    if version > SCHEMA_VERSION {
+        return Err(MigrationError::UnknownVersion(version));
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut value).map_err(|reason| MigrationError::Failed {
+            from: from as u32,
+            reason,
+        })?;
+        value["schema_version"] = json!(from + 1);
+    }
+
+    serde_json::from_value::<CurrentModel>(value)
+        .map(Model::from)
+        .map_err(|e| MigrationError::Invalid(e.to_string()))
+}
+
+// Version 1 added the seed and the random number generator.
+fn v0_add_seeded_rng(value: &mut Value) -> Result<(), String> {
+    let fresh = Model::default();
+
+    value["seed"] = json!(fresh.seed);
+    // Older serde_json can't turn the generator's u128 word position into a
+    // `Value` directly, but it can write and parse it.
+    let rng = serde_json::to_string(&fresh.rng).map_err(|e| e.to_string())?;
+    value["rng"] = serde_json::from_str(&rng).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn key_cargos_by_kind(cargos: &mut Value) -> Result<(), String> {
+    let fields = cargos
+        .as_object_mut()
+        .ok_or_else(|| String::from("cargos is not an object"))?;
+    for (field, kind) in [("food", "Food"), ("wood", "Wood"), ("sugar", "Sugar")] {
+        if let Some(cargo) = fields.remove(field) {
+            fields.insert(kind.to_string(), cargo);
+        }
+    }
+    Ok(())
+}
+
+// Version 2 turned the fixed food, wood and sugar cargo fields into a map
+// keyed by cargo kind.
+fn v1_key_cargos_by_kind(value: &mut Value) -> Result<(), String> {
+    key_cargos_by_kind(&mut value["player"]["ship"]["cargos"])?;
+    if !value["enemy"].is_null() {
+        key_cargos_by_kind(&mut value["enemy"]["ship"]["cargos"])?;
+    }
+    let ports = value["ports"]
+        .as_object_mut()
+        .ok_or_else(|| String::from("ports is not an object"))?;
+    for port in ports.values_mut() {
+        key_cargos_by_kind(&mut port["cargos"])?;
+    }
+    Ok(())
+}
+
+// Version 3 added the captain's log and the pending confirmation.
+fn v2_add_captains_log(value: &mut Value) -> Result<(), String> {
+    value["log"] = json!([]);
+    value["toasts_from"] = json!(0);
+    value["pending_confirmation"] = Value::Null;
+    Ok(())
+}
+
+fn clamp_ship(ship: &mut Value) {
+    for field in ["hull", "crew"] {
+        if matches!(ship[field].as_i64(), Some(v) if v < 0) {
+            ship[field] = json!(0);
+        }
+    }
+    if let Some(cargos) = ship["cargos"].as_object_mut() {
+        for cargo in cargos.values_mut() {
+            if matches!(cargo["unit"].as_i64(), Some(v) if v < 0) {
+                cargo["unit"] = json!(0);
+            }
+        }
+    }
+}
+
+// Version 4 started checking `invariants::check` on every import. Older games
+// could keep an enemy around after a skirmish, end one below zero hull or
+// crew, or loot an enemy below zero units of a cargo, so those are tidied up
+// rather than refused.
+fn v3_enforce_invariants(value: &mut Value) -> Result<(), String> {
+    let screen = serde_json::from_value::<Screen>(value["current_screen"].clone())
+        .map_err(|e| e.to_string())?;
+    match (screen.is_skirmish(), value["enemy"].is_null()) {
+        (false, false) => value["enemy"] = Value::Null,
+        (true, true) => value["current_screen"] = json!(Screen::MainNavigation),
+        _ => (),
+    }
+
+    clamp_ship(&mut value["player"]["ship"]);
+    if !value["enemy"].is_null() {
+        clamp_ship(&mut value["enemy"]["ship"]);
+    }
+    Ok(())
+}
+
+// Version 5 added the price history of every port, which starts empty.
+fn v4_add_price_history(value: &mut Value) -> Result<(), String> {
+    let ports = value["ports"]
+        .as_object_mut()
+        .ok_or_else(|| String::from("ports is not an object"))?;
+    for port in ports.values_mut() {
+        port["history"] = json!({});
+    }
+    Ok(())
+}
+
+// Version 6 added rum, tobacco, cloth, spices, gunpowder and medicine. Ports
+// of older games get them at the stock a new game starts with.
+fn v5_stock_new_goods(value: &mut Value) -> Result<(), String> {
+    let ports = value["ports"]
+        .as_object_mut()
+        .ok_or_else(|| String::from("ports is not an object"))?;
+    for (location, port) in ports.iter_mut() {
+        let location =
+            serde_json::from_value::<PortLocation>(json!(location)).map_err(|e| e.to_string())?;
+        let cargos = port["cargos"]
+            .as_object_mut()
+            .ok_or_else(|| String::from("cargos is not an object"))?;
+        for cargo in CONTENT.ports[&location].cargos.iter() {
+            let kind = cargo.kind.to_string();
+            if !cargos.contains_key(&kind) {
+                cargos.insert(
+                    kind,
+                    serde_json::to_value(cargo).map_err(|e| e.to_string())?,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+// Version 7 added black markets, found in the same ports as a new game.
+fn v6_add_black_markets(value: &mut Value) -> Result<(), String> {
+    let ports = value["ports"]
+        .as_object_mut()
+        .ok_or_else(|| String::from("ports is not an object"))?;
+    for (location, port) in ports.iter_mut() {
+        let location =
+            serde_json::from_value::<PortLocation>(json!(location)).map_err(|e| e.to_string())?;
+        port["black_market"] = json!(CONTENT.ports[&location].black_market);
+    }
+    Ok(())
+}
+
+// Version 8 added loans, and nobody owed a moneylender before.
+fn v7_add_loans(value: &mut Value) -> Result<(), String> {
+    value["player"]["loan"] = Value::Null;
+    Ok(())
+}
+
+// Version 9 logs catalog keys, so the log follows the player's language.
+// Entries written out in English before are kept as they are.
+fn v8_translate_log(value: &mut Value) -> Result<(), String> {
+    let log = value["log"]
+        .as_array_mut()
+        .ok_or_else(|| String::from("log is not an array"))?;
+    for entry in log.iter_mut() {
+        let text = entry["text"]
+            .as_str()
+            .ok_or_else(|| String::from("log entry text is not a string"))?;
+        entry["text"] =
+            serde_json::to_value(Text::notice(text.to_string())).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Questions waiting for an answer were written out in English too.
+fn v9_translate_questions(value: &mut Value) -> Result<(), String> {
+    let confirmation = &mut value["pending_confirmation"];
+    if confirmation.is_null() {
+        return Ok(());
+    }
+    notice(&mut confirmation["question"]);
+    upgrade_msg(&mut confirmation["msg"]);
+    Ok(())
+}
+
+// Wraps text written out before version 10 as a notice, and leaves text that
+// is already translatable alone.
+fn notice(value: &mut Value) {
+    if let Some(text) = value.as_str() {
+        *value = json!(Text::notice(text.to_string()));
+    }
+}
+
+// Journals recorded before version 2 carry a whole cargo in the market
+// messages rather than just its kind, and older journals trade a single unit
+// without saying how many. Before version 10 notices and questions were
+// English text.
+pub fn upgrade_msg(value: &mut Value) {
+    for variant in ["BuyCargo", "SellCargo"] {
+        if let Some(args) = value.get_mut(variant).and_then(|args| args.as_array_mut()) {
+            if let Some(kind) = args.get(1).and_then(|cargo| cargo.get("kind")).cloned() {
+                args[1] = kind;
+            }
+            if args.len() == 2 {
+                args.push(json!(1));
+            }
+        }
+    }
+    if let Some(args) = value.get_mut("Notify").and_then(|args| args.as_array_mut()) {
+        if let Some(text) = args.get_mut(1) {
+            notice(text);
+        }
+    }
+    if let Some(args) = value
+        .get_mut("AskConfirmation")
+        .and_then(|args| args.as_array_mut())
+    {
+        if let Some(question) = args.get_mut(0) {
+            notice(question);
+        }
+        if let Some(msg) = args.get_mut(1) {
+            upgrade_msg(msg);
+        }
+    }
+}
+
+// Same shape as `Model`. Deserialized once the JSON is upgraded to the
+// current schema, since `Model` itself deserializes through `migrate`.
+#[derive(Deserialize)]
+struct CurrentModel {
+    schema_version: u32,
+    date: NaiveDate,
+    current_screen: Screen,
+    player: Player,
+    ports: Ports,
+    current_port_location: PortLocation,
+    enemy: Option<Enemy>,
+    seed: u64,
+    rng: ChaCha8Rng,
+    log: Vec<LogEntry>,
+    toasts_from: usize,
+    pending_confirmation: Option<Confirmation>,
+}
+
+impl From<CurrentModel> for Model {
+    fn from(m: CurrentModel) -> Self {
+        Self {
+            schema_version: m.schema_version,
+            date: m.date,
+            current_screen: m.current_screen,
+            player: m.player,
+            ports: m.ports,
+            current_port_location: m.current_port_location,
+            enemy: m.enemy,
+            seed: m.seed,
+            rng: m.rng,
+            log: m.log,
+            toasts_from: m.toasts_from,
+            pending_confirmation: m.pending_confirmation,
+        }
+    }
+}
+
+impl TryFrom<Value> for Model {
+    type Error = MigrationError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        migrate(value)
+    }
+}
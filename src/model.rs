@@ -1,179 +1,69 @@
-use std::fmt::Display;
-use std::string::ParseError;
-use std::{collections::HashMap, str::FromStr};
+use std::fmt::{self, Display};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    str::FromStr,
+};
 use strum::IntoEnumIterator;
-use yewdux::prelude::*;
 
 use chrono::NaiveDate;
 use enum_display_derive::Display;
 use serde::{Deserialize, Serialize};
 
 use lazy_static::lazy_static;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 use strum_macros::EnumIter;
 
+use crate::content::Content;
+use crate::market;
+use crate::migrations::SCHEMA_VERSION;
+
 pub const MINIMUM_PLAYER_FOOD: i8 = 4;
 pub const MINIMUM_SHIP_HULL: i8 = 2;
 pub const MINIMUM_SHIP_CREW: i8 = 2;
+// Volleys roll damage up to the cannons less this, which must leave a range.
+pub const MINIMUM_SHIP_CANNONS: i8 = 4;
 pub const MINIMUM_PLAYER_AGE: i8 = 18;
 pub const MAXIMUM_PLAYER_AGE: i8 = 65;
+// Extra hull damage of a volley fired with a unit of gunpowder.
+pub const GUNPOWDER_DAMAGE: i16 = 2;
+// Older entries are dropped from the captain's log.
+pub const MAXIMUM_LOG_ENTRIES: usize = 100;
+// Older prices are dropped from a port's price history.
+pub const MAXIMUM_PRICE_HISTORY: usize = 30;
+// Enemy ships are named after one of these, see `i18n::ship_name`.
+pub const ENEMY_SHIP_NAMES: [&str; 5] = [
+    "Shady Wave",
+    "Palm West",
+    "Southern Seas",
+    "Morning Star",
+    "Blue Ocean",
+];
 
 lazy_static! {
-    pub static ref SHIPS: HashMap<ShipClass, Ship> = HashMap::from([
-        (
-            ShipClass::Cutter,
-            Ship {
-                class: ShipClass::Cutter,
-                crew: 8,
-                crew_capacity: 8,
-                hull: 40,
-                hull_capacity: 40,
-                cannons: 8,
-                cannons_capacity: 8,
-                cargos_capacity: 32,
-                price: 2100,
-                cargos: Cargos {
-                    food: Cargo {
-                        unit: 12,
-                        kind: CargoKind::Food,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-                ..Default::default()
-            }
-        ),
-        (
-            ShipClass::Sloop,
-            Ship {
-                class: ShipClass::Sloop,
-                crew: 14,
-                crew_capacity: 14,
-                hull: 62,
-                hull_capacity: 62,
-                cannons: 8,
-                cannons_capacity: 8,
-                cargos_capacity: 46,
-                price: 3200,
-                cargos: Cargos {
-                    food: Cargo {
-                        unit: 19,
-                        kind: CargoKind::Food,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-                ..Default::default()
-            }
-        ),
-        (
-            ShipClass::Brig,
-            Ship {
-                class: ShipClass::Brig,
-                crew: 18,
-                crew_capacity: 18,
-                hull: 70,
-                hull_capacity: 70,
-                cannons: 10,
-                cannons_capacity: 10,
-                cargos_capacity: 70,
-                price: 5000,
-                cargos: Cargos {
-                    food: Cargo {
-                        unit: 32,
-                        kind: CargoKind::Food,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-                ..Default::default()
-            }
-        ),
-        (
-            ShipClass::Junk,
-            Ship {
-                class: ShipClass::Junk,
-                crew: 24,
-                crew_capacity: 24,
-                hull: 70,
-                hull_capacity: 70,
-                cannons: 6,
-                cannons_capacity: 6,
-                cargos_capacity: 80,
-                price: 5500,
-                cargos: Cargos {
-                    food: Cargo {
-                        unit: 36,
-                        kind: CargoKind::Food,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-                ..Default::default()
-            }
-        ),
-        (
-            ShipClass::Galleon,
-            Ship {
-                crew: 32,
-                crew_capacity: 32,
-                hull: 90,
-                hull_capacity: 90,
-                cannons: 10,
-                cannons_capacity: 10,
-                cargos_capacity: 210,
-                class: ShipClass::Galleon,
-                price: 10_000,
-                cargos: Cargos {
-                    food: Cargo {
-                        unit: 60,
-                        kind: CargoKind::Food,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-                ..Default::default()
-            }
-        ),
-        (
-            ShipClass::Frigate,
-            Ship {
-                crew: 40,
-                crew_capacity: 40,
-                hull: 140,
-                hull_capacity: 140,
-                cannons: 14,
-                cannons_capacity: 14,
-                cargos_capacity: 150,
-                class: ShipClass::Frigate,
-                price: 35_000,
-                cargos: Cargos {
-                    food: Cargo {
-                        unit: 42,
-                        kind: CargoKind::Food,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-                ..Default::default()
-            }
-        ),
-    ]);
+    pub static ref CONTENT: Content = Content::embedded();
     pub static ref NATIONALITIES: Vec<Nationality> = Nationality::iter().collect();
     pub static ref SHIP_CLASSES: Vec<ShipClass> = ShipClass::iter().collect();
+    pub static ref PORT_LOCATIONS: Vec<PortLocation> = PortLocation::iter().collect();
+    pub static ref CARGO_KINDS: Vec<CargoKind> = CargoKind::iter().collect();
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Screen {
     MainNavigation,
     About,
     #[default]
     MainMenu,
+    SaveSlots,
+    ImportSave,
     NewCharacter,
     Profile,
     Dock,
     DockTavern,
     TavernHireCrew,
+    TavernMoneylender,
     DockMarket,
+    DockBlackMarket,
     DockShipyard,
     Skirmish,
     SkirmishChase,
@@ -182,15 +72,26 @@ pub enum Screen {
     GameLost(GameLostReason),
 }
 
-#[derive(Default, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+impl Screen {
+    // The screens where the player faces an enemy.
+    pub fn is_skirmish(&self) -> bool {
+        matches!(
+            self,
+            Self::Skirmish | Self::SkirmishChase | Self::SkirmishBattle | Self::SkirmishLoot
+        )
+    }
+}
+
+#[derive(Default, Debug, EnumIter, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum GameLostReason {
     #[default]
     ShipSunk,
     AllCrewDied,
     FoodMutiny,
+    Bankruptcy,
 }
 
-#[derive(Default, EnumIter, Display, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(Default, Debug, EnumIter, Display, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Nationality {
     #[default]
     British,
@@ -198,47 +99,141 @@ pub enum Nationality {
     French,
 }
 
+// A name that none of the variants of an enum goes by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownName {
+    pub what: &'static str,
+    pub name: String,
+}
+
+impl fmt::Display for UnknownName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown {} \"{}\"", self.what, self.name)
+    }
+}
+
+fn unknown_name<T>(what: &'static str, name: &str) -> Result<T, UnknownName> {
+    Err(UnknownName {
+        what,
+        name: name.to_string(),
+    })
+}
+
 impl FromStr for Nationality {
-    type Err = ParseError;
+    type Err = UnknownName;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
+            "British" => Self::British,
             "Spanish" => Self::Spanish,
             "French" => Self::French,
-            _otherwise => Self::British,
+            _otherwise => return unknown_name("nationality", s),
         })
     }
 }
 
-#[derive(Default, EnumIter, Display, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(
+    Default,
+    Debug,
+    EnumIter,
+    Display,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Deserialize,
+    Serialize,
+)]
 pub enum CargoKind {
     #[default]
     Food,
     Wood,
     Sugar,
+    Rum,
+    Tobacco,
+    Cloth,
+    Spices,
+    // Spent to make the player's volleys hit harder.
+    Gunpowder,
+    // Spent to save half the crew lost in a boarding fight.
+    Medicine,
+}
+
+// What a kind of cargo is like, wherever it is traded.
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CargoInfo {
+    // Price used by ports that don't trade this kind themselves.
+    pub base_price: i32,
+    // Perishable cargo may lose a unit on every voyage.
+    pub perishable: bool,
+    // Ports of these nations treat the good as contraband, see `customs`.
+    pub banned_by: Vec<Nationality>,
+    // Stock a medium sized port aims to hold, see `market::target_units`.
+    pub target_units: i32,
+    // Range of units an enemy ship carries.
+    pub loot_units: (i32, i32),
+    // Ports of these nations make the good, and keep more of it for less.
+    pub produced_by: Vec<Nationality>,
+    // Ports of these nations are short of the good, and pay more for it.
+    pub demanded_by: Vec<Nationality>,
+    // Large and huge ports that don't produce a luxury demand it.
+    pub luxury: bool,
 }
 
-#[derive(Default, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(Default, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Cargo {
     pub price: i32,
     pub unit: i32,
     pub kind: CargoKind,
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
-pub struct Cargos {
-    pub food: Cargo,
-    pub wood: Cargo,
-    pub sugar: Cargo,
-}
+// Inventory of a ship or a port. Kinds that were never stocked count as
+// zero units at no price; ports are stocked with every kind when the content
+// is loaded, so their prices always come from the content they were built with.
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Cargos(BTreeMap<CargoKind, Cargo>);
 
 impl Cargos {
+    pub fn get(&self, kind: CargoKind) -> Cargo {
+        self.0.get(&kind).copied().unwrap_or(Cargo {
+            price: 0,
+            unit: 0,
+            kind,
+        })
+    }
+
+    pub fn get_mut(&mut self, kind: CargoKind) -> &mut Cargo {
+        let cargo = self.get(kind);
+        self.0.entry(kind).or_insert(cargo)
+    }
+
+    pub fn unit(&self, kind: CargoKind) -> i32 {
+        self.get(kind).unit
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Cargo> {
+        self.0.values()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Cargo> {
+        self.0.values_mut()
+    }
+
     pub fn total_unit(&self) -> i32 {
-        self.food.unit + self.wood.unit + self.sugar.unit
+        self.iter().map(|c| c.unit).sum()
+    }
+}
+
+impl FromIterator<Cargo> for Cargos {
+    fn from_iter<T: IntoIterator<Item = Cargo>>(iter: T) -> Self {
+        Self(iter.into_iter().map(|c| (c.kind, c)).collect())
     }
 }
 
 #[derive(
-    Default, Copy, Display, EnumIter, Hash, Clone, PartialEq, Eq, Deserialize, Serialize, Store,
+    Default, Debug, Copy, Display, EnumIter, Hash, Clone, PartialEq, Eq, Deserialize, Serialize,
 )]
 pub enum ShipClass {
     Cutter,
@@ -251,21 +246,22 @@ pub enum ShipClass {
 }
 
 impl FromStr for ShipClass {
-    type Err = ParseError;
+    type Err = UnknownName;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
+            "Sloop" => Self::Sloop,
             "Cutter" => Self::Cutter,
             "Brig" => Self::Brig,
             "Junk" => Self::Junk,
             "Galleon" => Self::Galleon,
             "Frigate" => Self::Frigate,
-            _otherwise => Self::Sloop,
+            _otherwise => return unknown_name("ship class", s),
         })
     }
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Ship {
     pub name: String,
     pub class: ShipClass,
@@ -290,9 +286,18 @@ impl Ship {
         let each_crew_member_cost = 8;
         each_crew_member_cost * (self.crew_capacity - self.crew)
     }
+
+    // Neither hull nor crew can go below zero.
+    pub fn take_damage(&mut self, hull: i16) {
+        self.hull = (self.hull - hull).max(0);
+    }
+
+    pub fn lose_crew(&mut self, crew: i32) {
+        self.crew = (self.crew - crew).max(0);
+    }
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum EnemyMovement {
     Chase,
     #[default]
@@ -300,7 +305,7 @@ pub enum EnemyMovement {
     Evade,
 }
 
-#[derive(Default, Display, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(Default, Display, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum EnemyDistance {
     Escape,
     #[default]
@@ -309,7 +314,7 @@ pub enum EnemyDistance {
     Board,
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Enemy {
     pub ship: Ship,
     pub nationality: Nationality,
@@ -317,7 +322,7 @@ pub struct Enemy {
     pub distance: EnemyDistance,
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum PortPopulation {
     Small,
     #[default]
@@ -326,25 +331,67 @@ pub enum PortPopulation {
     Huge,
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+impl PortPopulation {
+    // How much of every good the port wants to hold, in percent of a medium
+    // sized port.
+    pub fn market_size(&self) -> i32 {
+        match self {
+            Self::Small => 50,
+            Self::Medium => 100,
+            Self::Large => 150,
+            Self::Huge => 200,
+        }
+    }
+}
+
+// Market price and stock of a good at the end of a day.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PricePoint {
+    pub date: NaiveDate,
+    pub price: i32,
+    pub unit: i32,
+}
+
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Port {
     pub name: String,
     pub description: String,
     pub population: PortPopulation,
     pub nationality: Nationality,
+    // Contraband can be traded on the `DockBlackMarket` screen.
+    pub black_market: bool,
     pub cargos: Cargos,
+    // Oldest first, see `market::record`.
+    pub history: BTreeMap<CargoKind, VecDeque<PricePoint>>,
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+// Coins owed to a moneylender, see `lending`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Loan {
+    // Port whose moneylender lent the coins.
+    pub lender: PortLocation,
+    // Borrowed coins plus the interest so far.
+    pub owed: i32,
+    pub due: NaiveDate,
+    // Interest is compounded up to this date.
+    pub accrued_on: NaiveDate,
+    // Due dates missed so far, each one worse than the last.
+    pub missed: u8,
+}
+
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Player {
     pub name: String,
     pub age: i8,
     pub nationality: Nationality,
     pub coins: i32,
     pub ship: Ship,
+    pub loan: Option<Loan>,
 }
 
-#[derive(Default, Display, Copy, Hash, Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
+#[derive(
+    Default, Debug, Display, Copy, EnumIter, Hash, Clone, PartialEq, Eq, Deserialize, Serialize,
+)]
 pub enum PortLocation {
     Barbados,
     #[default]
@@ -354,125 +401,199 @@ pub enum PortLocation {
 
 pub type Ports = HashMap<PortLocation, Port>;
 
-#[derive(Clone, PartialEq, Eq, Deserialize, Serialize, Store)]
-#[store(storage = "local", storage_tab_sync)]
+// Saved games are upgraded through `migrations::migrate` when loaded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Danger,
+}
+
+// Player-facing text kept as a catalog key and its arguments, so that it is
+// shown in the language chosen when it is read. See `i18n::render`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Text {
+    pub key: String,
+    pub args: Vec<(String, Arg)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Arg {
+    // Shown as it is, like a number or a date.
+    Plain(String),
+    // Translated in turn, like a port or a cargo kind.
+    Text(Text),
+    // Translated in turn and listed one after another.
+    Texts(Vec<Text>),
+}
+
+impl Text {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    // Text that was written out before it reached the log, like an older
+    // save's log or a notice from the English-only terminal frontend.
+    pub fn notice(text: String) -> Self {
+        Self::new("log.notice").arg("text", text)
+    }
+
+    pub fn arg(self, name: &str, value: impl Display) -> Self {
+        self.with(name, Arg::Plain(value.to_string()))
+    }
+
+    pub fn text(self, name: &str, value: Text) -> Self {
+        self.with(name, Arg::Text(value))
+    }
+
+    // Left out when there is nothing to say.
+    pub fn optional(self, name: &str, value: Option<Text>) -> Self {
+        match value {
+            Some(value) => self.text(name, value),
+            None => self.arg(name, ""),
+        }
+    }
+
+    pub fn texts(self, name: &str, values: Vec<Text>) -> Self {
+        self.with(name, Arg::Texts(values))
+    }
+
+    fn with(mut self, name: &str, arg: Arg) -> Self {
+        self.args.push((name.to_string(), arg));
+        self
+    }
+}
+
+// An entry of the captain's log, dated in game time.
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LogEntry {
+    // Increases with every entry, even after older ones are dropped.
+    pub id: u64,
+    pub date: NaiveDate,
+    pub severity: Severity,
+    pub text: Text,
+}
+
+// A message held back until the player answers the question.
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Confirmation {
+    pub question: Text,
+    pub msg: Box<Msg>,
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "web",
+    derive(yewdux::prelude::Store),
+    store(storage = "local", storage_tab_sync)
+)]
+#[serde(try_from = "serde_json::Value")]
 pub struct Model {
+    pub schema_version: u32,
     pub date: NaiveDate,
     pub current_screen: Screen,
     pub player: Player,
     pub ports: Ports,
     pub current_port_location: PortLocation,
     pub enemy: Option<Enemy>,
+    pub seed: u64,
+    // Every random roll of the game is drawn from here, and it is saved
+    // together with the rest of the model to keep a game reproducible.
+    pub rng: ChaCha8Rng,
+    pub log: Vec<LogEntry>,
+    // Entries from here on were added by the last action, and are shown as toasts.
+    pub toasts_from: usize,
+    pub pending_confirmation: Option<Confirmation>,
 }
 
-// Initializer for our whole model at launch
-impl Default for Model {
-    fn default() -> Self {
-        let mut player_ship = SHIPS.get(&ShipClass::Sloop).unwrap().clone();
+impl Model {
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+    }
+
+    pub fn log(&mut self, severity: Severity, text: Text) {
+        let id = self.log.last().map_or(0, |e| e.id + 1);
+        self.log.push(LogEntry {
+            id,
+            date: self.date,
+            severity,
+            text,
+        });
+
+        if self.log.len() > MAXIMUM_LOG_ENTRIES {
+            self.log.remove(0);
+            self.toasts_from = self.toasts_from.saturating_sub(1);
+        }
+    }
+
+    pub fn toasts(&self) -> &[LogEntry] {
+        &self.log[self.toasts_from.min(self.log.len())..]
+    }
+
+    pub fn with_content(content: &Content) -> Self {
+        let mut player_ship = content.ships.get(&ShipClass::Sloop).unwrap().clone();
         player_ship.name = String::from("Luna");
+        let date = NaiveDate::from_ymd_opt(1680, 1, 1).unwrap();
+        let mut ports = content.ports.clone();
+        for (location, port) in ports.iter_mut() {
+            market::reprice(content, *location, port);
+            market::record(port, date);
+        }
+        let seed = rand::random();
         Self {
-            date: NaiveDate::from_ymd(1680, 1, 1),
+            schema_version: SCHEMA_VERSION,
+            date,
             player: Player {
                 name: String::from("Player"),
                 age: 18,
                 nationality: Nationality::British,
                 coins: 25_000,
                 ship: player_ship,
+                loan: None,
             },
-            ports: HashMap::from([
-                (
-                    PortLocation::Barbados,
-                    Port {
-                        name: String::from("Barbados"),
-                        description: String::from("A large port, primarily known as the sugar plantation & trading paradise. Currently, controlled by the Spanish crown."),
-                        population: PortPopulation::Large,
-                        nationality: Nationality::Spanish,
-                        cargos: Cargos {
-                            food: Cargo {
-                                price: 8,
-                                unit: 250,
-                                kind: CargoKind::Food,
-                            },
-                            wood: Cargo {
-                                price: 22,
-                                unit: 250,
-                                kind: CargoKind::Wood,
-                            },
-                            sugar: Cargo {
-                                price: 30,
-                                unit: 250,
-                                kind: CargoKind::Sugar,
-                            },
-                        },
-                    },
-                ),
-                (
-                    PortLocation::PortRoyal,
-                    Port {
-                        name: String::from("Port Royal"),
-                        description: String::from("A huge port with a wealthy, prosperous population. Controlled by the British empire."),
-                        population: PortPopulation::Huge,
-                        nationality: Nationality::British,
-                        cargos: Cargos {
-                            food: Cargo {
-                                price: 5,
-                                unit: 250,
-                                kind: CargoKind::Food,
-                            },
-                            wood: Cargo {
-                                price: 18,
-                                unit: 210,
-                                kind: CargoKind::Wood,
-                            },
-                            sugar: Cargo {
-                                price: 50,
-                                unit: 120,
-                                kind: CargoKind::Sugar,
-                            },
-                        },
-                    },
-                ),
-                (
-                    PortLocation::Nassau,
-                    Port {
-                        name: String::from("Nassau"),
-                        description: String::from("A medium port, dwelled by legendary figures. Currently in blockade by the British for the high piracy activity."),
-                        population: PortPopulation::Medium,
-                        nationality: Nationality::British,
-                        cargos: Cargos {
-                            food: Cargo {
-                                price: 10,
-                                unit: 250,
-                                kind: CargoKind::Food,
-                            },
-                            wood: Cargo {
-                                price: 32,
-                                unit: 150,
-                                kind: CargoKind::Wood,
-                            },
-                            sugar: Cargo {
-                                price: 20,
-                                unit: 180,
-                                kind: CargoKind::Sugar,
-                            },
-                        },
-                    },
-                ),
-            ]),
+            ports,
             current_screen: Screen::default(),
             current_port_location: PortLocation::default(),
             enemy: None,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            log: Vec::new(),
+            toasts_from: 0,
+            pending_confirmation: None,
         }
     }
 }
 
+// Initializer for our whole model at launch
+impl Default for Model {
+    fn default() -> Self {
+        Self::with_content(&CONTENT)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Msg {
-    // TODO: Remove ResetModel when debug isn't needed anymore!!!
+    // Start over from a fresh game.
     ResetModel,
+    SetPlayerName(String),
+    SetPlayerAge(i8),
+    SetPlayerNationality(Nationality),
+    SetShipName(String),
+    SetShipClass(ShipClass),
+    SetSeed(u64),
     SwitchScreen(Screen),
     SwitchPlayerLocation(PortLocation),
-    BuyCargo(PortLocation, Cargo),
-    SellCargo(PortLocation, Cargo),
+    // Trade this many units in one go.
+    BuyCargo(PortLocation, CargoKind, i32),
+    SellCargo(PortLocation, CargoKind, i32),
+    // Trade contraband on the port's black market.
+    BuyContraband(PortLocation, CargoKind, i32),
+    SellContraband(PortLocation, CargoKind, i32),
     SkirmishChaseClose,
     SkirmishChaseDistant,
     SkirmishChaseBroadside,
@@ -480,6 +601,30 @@ pub enum Msg {
     SkirmishBattleShootFalconet,
     RepairShip(i32),
     HireCrew(i32),
+    // Borrow from or repay the moneylender this many coins.
+    TakeLoan(i32),
+    RepayLoan(i32),
     TakeEnemyCargo(CargoKind),
     BuyAndReplaceShip(ShipClass),
+    Notify(Severity, Text),
+    DismissToasts,
+    // Hold `Msg` back until the player confirms or cancels it.
+    AskConfirmation(Text, Box<Msg>),
+    Confirm,
+    CancelConfirmation,
+    #[cfg(feature = "dev-tools")]
+    Dev(crate::dev::DevCommand),
+}
+
+impl Msg {
+    // Messages that only change what is shown, and so are not worth saving.
+    pub fn is_ui_only(&self) -> bool {
+        matches!(
+            self,
+            Msg::Notify(..)
+                | Msg::DismissToasts
+                | Msg::AskConfirmation(..)
+                | Msg::CancelConfirmation
+        )
+    }
 }
@@ -0,0 +1,34 @@
+//! Finds saves in the browser storage that can no longer be loaded, so the
+//! player gets a chance to keep a copy before they are replaced.
+
+use std::any::type_name;
+
+use yewdux::prelude::*;
+
+use lunar::model::Model;
+use lunar::saves::{read_model, BrokenSave, Storage};
+use lunar::web::LocalStorage;
+
+#[derive(Default, Clone, PartialEq, Eq, Store)]
+pub struct Recovery {
+    pub broken: Vec<BrokenSave>,
+}
+
+// The current game is kept under its type name in the local storage.
+fn check_current_game() -> Option<BrokenSave> {
+    let raw = LocalStorage.get(type_name::<Model>())?;
+
+    read_model(&raw).err().map(|error| BrokenSave {
+        label: String::from("Current game"),
+        raw,
+        error,
+    })
+}
+
+// Save slots are checked when they are loaded, see `Saves::load`.
+pub fn find_broken_saves(broken_slots: Vec<BrokenSave>) -> Vec<BrokenSave> {
+    check_current_game()
+        .into_iter()
+        .chain(broken_slots)
+        .collect()
+}
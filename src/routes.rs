@@ -0,0 +1,62 @@
+//! Hash routes for screens, so that the browser's back and forward buttons,
+//! reloads and bookmarks move between screens like they would on any page.
+//! Which screen a route leads to is decided in `lunar::routing`.
+
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::window;
+use yewdux::prelude::*;
+
+use crate::recovery::Recovery;
+use lunar::model::*;
+use lunar::routing::{self, route_of};
+
+fn current_hash() -> String {
+    window().unwrap().location().hash().unwrap_or_default()
+}
+
+// Record a screen the game moved to as a new browser history entry.
+pub fn sync_url(screen: &Screen) {
+    let route = route_of(screen);
+    if current_hash() != route {
+        let history = window().unwrap().history().unwrap();
+        history
+            .push_state_with_url(&JsValue::NULL, "", Some(route))
+            .unwrap();
+    }
+}
+
+// Swap a route the game cannot show for the one of the screen it is on.
+fn redirect(route: &str) {
+    let history = window().unwrap().history().unwrap();
+    history
+        .replace_state_with_url(&JsValue::NULL, "", Some(route))
+        .unwrap();
+}
+
+// Move to the screen in the URL, or put the URL back if that is not allowed.
+pub fn follow_url() {
+    // Following the URL is an action, which would overwrite broken saves.
+    if !Dispatch::<Recovery>::new().get().broken.is_empty() {
+        return;
+    }
+
+    let dispatch = Dispatch::<Model>::new();
+    let current = dispatch.get().current_screen.clone();
+    let redirected = routing::follow(&current, &current_hash(), |msg| {
+        dispatch.apply(msg);
+        dispatch.get().current_screen.clone()
+    });
+    if let Some(route) = redirected {
+        redirect(route);
+    }
+}
+
+// Follow the URL whenever the player goes back or forward, or edits it.
+pub fn listen() {
+    let on_hash_change = Closure::<dyn Fn()>::new(follow_url);
+    window()
+        .unwrap()
+        .add_event_listener_with_callback("hashchange", on_hash_change.as_ref().unchecked_ref())
+        .unwrap();
+    on_hash_change.forget();
+}
@@ -0,0 +1,81 @@
+//! Hash routes for screens, and which of them a link may lead to. The
+//! browser side, listening to the URL and writing it back, is in
+//! `src/routes.rs`.
+
+use crate::model::*;
+
+pub const ROUTES: [(&str, Screen); 22] = [
+    ("#/", Screen::MainMenu),
+    ("#/about", Screen::About),
+    ("#/saves", Screen::SaveSlots),
+    ("#/import", Screen::ImportSave),
+    ("#/new-character", Screen::NewCharacter),
+    ("#/navigation", Screen::MainNavigation),
+    ("#/profile", Screen::Profile),
+    ("#/dock", Screen::Dock),
+    ("#/dock/tavern", Screen::DockTavern),
+    ("#/dock/tavern/hire-crew", Screen::TavernHireCrew),
+    ("#/dock/tavern/moneylender", Screen::TavernMoneylender),
+    ("#/dock/market", Screen::DockMarket),
+    ("#/dock/black-market", Screen::DockBlackMarket),
+    ("#/dock/shipyard", Screen::DockShipyard),
+    ("#/skirmish", Screen::Skirmish),
+    ("#/skirmish/chase", Screen::SkirmishChase),
+    ("#/skirmish/battle", Screen::SkirmishBattle),
+    ("#/skirmish/loot", Screen::SkirmishLoot),
+    (
+        "#/lost/ship-sunk",
+        Screen::GameLost(GameLostReason::ShipSunk),
+    ),
+    (
+        "#/lost/crew-died",
+        Screen::GameLost(GameLostReason::AllCrewDied),
+    ),
+    (
+        "#/lost/mutiny",
+        Screen::GameLost(GameLostReason::FoodMutiny),
+    ),
+    (
+        "#/lost/bankruptcy",
+        Screen::GameLost(GameLostReason::Bankruptcy),
+    ),
+];
+
+pub fn route_of(screen: &Screen) -> &'static str {
+    ROUTES
+        .iter()
+        .find(|(_, s)| s == screen)
+        .map_or(ROUTES[0].0, |(route, _)| route)
+}
+
+pub fn screen_of(route: &str) -> Option<Screen> {
+    ROUTES
+        .iter()
+        .find(|(r, _)| *r == route)
+        .map(|(_, screen)| screen.clone())
+}
+
+// Creating a character or starting a skirmish only happens through play, never
+// by following a link. Any other screen the game is not in a state for, like
+// looting with no enemy around, is refused by `transitions::check`.
+pub fn reachable_by_url(screen: &Screen) -> bool {
+    !matches!(screen, Screen::NewCharacter | Screen::Skirmish)
+}
+
+// Move from `current` to the screen in `hash` through `apply`, which hands
+// back the screen the game is on afterwards. Returns the route to put in the
+// URL instead when the game did not end up on the screen of the hash.
+pub fn follow(
+    current: &Screen,
+    hash: &str,
+    apply: impl FnOnce(Msg) -> Screen,
+) -> Option<&'static str> {
+    match screen_of(hash) {
+        Some(screen) if screen == *current => None,
+        Some(screen) if reachable_by_url(&screen) => {
+            let now = apply(Msg::SwitchScreen(screen.clone()));
+            (now != screen).then(|| route_of(&now))
+        }
+        _ => Some(route_of(current)),
+    }
+}
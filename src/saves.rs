@@ -0,0 +1,345 @@
+//! Named save slots, so a browser can keep more than one game around.
+//!
+//! Every slot is stored under keys of its own, next to an index of the
+//! slots, so saving after an action only writes the game being played.
+
+use std::cmp::Reverse;
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::invariants;
+use crate::journal::Journal;
+use crate::model::*;
+
+pub fn now() -> NaiveDateTime {
+    Local::now().naive_local()
+}
+
+/// Where the slots are kept: the browser's local storage on the web.
+pub trait Storage {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&mut self, key: &str, value: &str);
+    fn remove(&mut self, key: &str);
+}
+
+pub const INDEX_KEY: &str = "lunar.saves";
+// Everything was kept in one entry under the store's type name before.
+pub const LEGACY_KEY: &str = "lunar::saves::Saves";
+const LEGACY_JOURNAL_KEY: &str = "lunar::journal::Journal";
+
+pub fn model_key(id: u32) -> String {
+    format!("lunar.saves.{}.model", id)
+}
+
+// A game that has no slot yet keeps its journal apart too.
+pub fn journal_key(id: Option<u32>) -> String {
+    match id {
+        Some(id) => format!("lunar.saves.{}.journal", id),
+        None => String::from("lunar.journal"),
+    }
+}
+
+// Stored text that can no longer be loaded, kept as it is so the player can
+// download a copy.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BrokenSave {
+    pub label: String,
+    pub raw: String,
+    pub error: String,
+}
+
+// A stored game, upgraded to the current schema and checked like an imported
+// one, since playing on a game that breaks an invariant would panic in debug
+// builds.
+pub fn read_model(raw: &str) -> Result<Model, String> {
+    let model = serde_json::from_str::<Model>(raw).map_err(|e| e.to_string())?;
+    invariants::check(&model).map_err(|v| v.to_string())?;
+    Ok(model)
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SaveSlot {
+    pub id: u32,
+    pub name: String,
+    pub last_played: NaiveDateTime,
+    pub model: Model,
+    pub journal: Journal,
+}
+
+impl SaveSlot {
+    pub fn captain(&self) -> &str {
+        &self.model.player.name
+    }
+
+    pub fn ship(&self) -> &Ship {
+        &self.model.player.ship
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.model.date
+    }
+
+    pub fn coins(&self) -> i32 {
+        self.model.player.coins
+    }
+
+    fn write(&self, storage: &mut impl Storage) {
+        storage.set(
+            &model_key(self.id),
+            &serde_json::to_string(&self.model).unwrap(),
+        );
+        storage.set(
+            &journal_key(Some(self.id)),
+            &serde_json::to_string(&self.journal).unwrap(),
+        );
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct SlotHeader {
+    id: u32,
+    name: String,
+    last_played: NaiveDateTime,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct Index {
+    slots: Vec<SlotHeader>,
+    active: Option<u32>,
+    next_id: u32,
+}
+
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "web", derive(yewdux::prelude::Store))]
+pub struct Saves {
+    pub slots: Vec<SaveSlot>,
+    // Slot the current game is saved into after every action.
+    pub active: Option<u32>,
+    next_id: u32,
+    // Slots that failed to load stay listed in the index, so their keys are
+    // still there once the problem is fixed.
+    #[serde(skip)]
+    unreadable: Vec<SlotHeader>,
+    // Slot the player asked to delete, until they confirm or cancel.
+    #[serde(skip)]
+    pub pending_deletion: Option<u32>,
+}
+
+impl Saves {
+    // Slots that fail to load are reported and left out, but their keys are
+    // never written over.
+    pub fn load(storage: &mut impl Storage) -> (Self, Vec<BrokenSave>) {
+        let mut broken = Vec::new();
+        if storage.get(INDEX_KEY).is_none() {
+            if let Some(raw) = storage.get(LEGACY_KEY) {
+                match serde_json::from_str::<Saves>(&raw) {
+                    Ok(saves) => saves.write_all(storage),
+                    Err(e) => broken.push(BrokenSave {
+                        label: String::from("Save slots"),
+                        raw,
+                        error: e.to_string(),
+                    }),
+                }
+            }
+        }
+        let index = storage
+            .get(INDEX_KEY)
+            .and_then(|raw| serde_json::from_str::<Index>(&raw).ok())
+            .unwrap_or_default();
+
+        let mut saves = Saves {
+            next_id: index.next_id,
+            ..Default::default()
+        };
+        for header in index.slots {
+            let raw = storage.get(&model_key(header.id)).unwrap_or_default();
+            let model = match read_model(&raw) {
+                Ok(model) => model,
+                Err(error) => {
+                    broken.push(BrokenSave {
+                        label: format!("Save slot {}", header.name),
+                        raw,
+                        error,
+                    });
+                    saves.unreadable.push(header);
+                    continue;
+                }
+            };
+            // A journal that cannot be read only stops the game from being
+            // verified, so it is started over rather than losing the game.
+            let journal = storage
+                .get(&journal_key(Some(header.id)))
+                .and_then(|raw| serde_json::from_str::<Journal>(&raw).ok())
+                .unwrap_or_else(|| Journal::starting_from(&model));
+            saves.slots.push(SaveSlot {
+                id: header.id,
+                name: header.name,
+                last_played: header.last_played,
+                model,
+                journal,
+            });
+        }
+        saves.active = index.active.filter(|&id| saves.get(id).is_some());
+
+        (saves, broken)
+    }
+
+    // Journal of the game being played, whether it has a slot or not. A game
+    // whose journal was lost starts a new one from where it is.
+    pub fn current_journal(&self, storage: &impl Storage, model: &Model) -> Journal {
+        match self.active_slot() {
+            Some(slot) => slot.journal.to_owned(),
+            None => [journal_key(None), String::from(LEGACY_JOURNAL_KEY)]
+                .iter()
+                .find_map(|key| serde_json::from_str(&storage.get(key)?).ok())
+                .unwrap_or_else(|| Journal::starting_from(model)),
+        }
+    }
+
+    fn write_index(&self, storage: &mut impl Storage) {
+        let index = Index {
+            slots: self
+                .slots
+                .iter()
+                .map(|s| SlotHeader {
+                    id: s.id,
+                    name: s.name.to_string(),
+                    last_played: s.last_played,
+                })
+                .chain(self.unreadable.iter().cloned())
+                .collect(),
+            active: self.active,
+            next_id: self.next_id,
+        };
+        storage.set(INDEX_KEY, &serde_json::to_string(&index).unwrap());
+    }
+
+    // Moves everything kept under the legacy key to keys of their own.
+    fn write_all(&self, storage: &mut impl Storage) {
+        for slot in self.slots.iter() {
+            slot.write(storage);
+        }
+        self.write_index(storage);
+        storage.remove(LEGACY_KEY);
+    }
+
+    pub fn get(&self, id: u32) -> Option<&SaveSlot> {
+        self.slots.iter().find(|s| s.id == id)
+    }
+
+    pub fn active_slot(&self) -> Option<&SaveSlot> {
+        self.active.and_then(|id| self.get(id))
+    }
+
+    // Most recently played first.
+    pub fn sorted(&self) -> Vec<&SaveSlot> {
+        let mut slots = self.slots.iter().collect::<Vec<&SaveSlot>>();
+        slots.sort_by_key(|s| Reverse(s.last_played));
+        slots
+    }
+
+    pub fn create(
+        &mut self,
+        storage: &mut impl Storage,
+        name: String,
+        model: &Model,
+        journal: &Journal,
+        now: NaiveDateTime,
+    ) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let slot = SaveSlot {
+            id,
+            name,
+            last_played: now,
+            model: model.to_owned(),
+            journal: journal.to_owned(),
+        };
+        slot.write(storage);
+        self.slots.push(slot);
+        self.active = Some(id);
+        self.write_index(storage);
+        storage.remove(&journal_key(None));
+
+        id
+    }
+
+    pub fn activate(&mut self, storage: &mut impl Storage, id: Option<u32>) {
+        self.active = id.filter(|&id| self.get(id).is_some());
+        self.write_index(storage);
+    }
+
+    // Only the active slot is written, or the journal of a game that has no
+    // slot yet.
+    pub fn save_active(
+        &mut self,
+        storage: &mut impl Storage,
+        model: &Model,
+        journal: &Journal,
+        now: NaiveDateTime,
+    ) {
+        let id = self.active;
+        match self.slots.iter_mut().find(|s| Some(s.id) == id) {
+            Some(slot) => {
+                slot.model = model.to_owned();
+                slot.journal = journal.to_owned();
+                slot.last_played = now;
+                slot.write(storage);
+                self.write_index(storage);
+            }
+            None => storage.set(&journal_key(None), &serde_json::to_string(journal).unwrap()),
+        }
+    }
+
+    pub fn duplicate(
+        &mut self,
+        storage: &mut impl Storage,
+        id: u32,
+        now: NaiveDateTime,
+    ) -> Option<u32> {
+        let mut copy = self.get(id)?.to_owned();
+        copy.id = self.next_id;
+        copy.name = format!("{} (copy)", copy.name);
+        copy.last_played = now;
+        self.next_id += 1;
+        copy.write(storage);
+        self.slots.push(copy);
+        self.write_index(storage);
+
+        Some(self.next_id - 1)
+    }
+
+    pub fn rename(&mut self, storage: &mut impl Storage, id: u32, name: String) {
+        if let Some(slot) = self.slots.iter_mut().find(|s| s.id == id) {
+            slot.name = name;
+            self.write_index(storage);
+        }
+    }
+
+    pub fn delete(&mut self, storage: &mut impl Storage, id: u32) {
+        self.slots.retain(|s| s.id != id);
+        if self.active == Some(id) {
+            self.active = None;
+        }
+        self.write_index(storage);
+        storage.remove(&model_key(id));
+        storage.remove(&journal_key(Some(id)));
+    }
+
+    // Deleting can't be undone, so the player is asked first, like before any
+    // other action that can't.
+    pub fn ask_deletion(&mut self, id: u32) {
+        self.pending_deletion = self.get(id).map(|slot| slot.id);
+    }
+
+    pub fn confirm_deletion(&mut self, storage: &mut impl Storage) {
+        if let Some(id) = self.pending_deletion.take() {
+            self.delete(storage, id);
+        }
+    }
+
+    pub fn cancel_deletion(&mut self) {
+        self.pending_deletion = None;
+    }
+}
@@ -0,0 +1,15 @@
+//! Player preferences, kept in the browser apart from any saved game.
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Locale;
+
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "web",
+    derive(yewdux::prelude::Store),
+    store(storage = "local", storage_tab_sync)
+)]
+pub struct Settings {
+    pub locale: Locale,
+}
@@ -0,0 +1,428 @@
+//! Headless balance simulation: bots play many games against the engine, and
+//! the outcomes are summed up in a [`Report`] to tune the game numbers with.
+
+use std::{collections::BTreeMap, fmt};
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::customs;
+use crate::engine;
+use crate::market::{self, Venue};
+use crate::model::*;
+
+// Days at which the coins of every game still afloat are sampled.
+pub const CHECKPOINT_DAYS: [i64; 4] = [30, 90, 180, 365];
+
+/// Picks the next message for a bot. A fresh strategy plays every game.
+pub trait Strategy {
+    fn next_msg(&mut self, model: &Model, rng: &mut ChaCha8Rng) -> Msg;
+}
+
+pub type NewStrategy = fn() -> Box<dyn Strategy>;
+
+pub const STRATEGIES: [(&str, NewStrategy); 3] = [
+    ("trader", || Box::new(GreedyTrader)),
+    ("pirate", || Box::new(Pirate::default())),
+    ("random", || Box::new(RandomPlayer)),
+];
+
+// Food kept in the hold before doing anything else, so a bot does not lose
+// to a mutiny right away.
+const FOOD_RESERVE: i32 = MINIMUM_PLAYER_FOOD as i32 + 6;
+
+fn sail_anywhere(model: &Model, rng: &mut ChaCha8Rng) -> Msg {
+    let others = PORT_LOCATIONS
+        .iter()
+        .filter(|&&l| l != model.current_port_location)
+        .copied()
+        .collect::<Vec<PortLocation>>();
+    Msg::SwitchPlayerLocation(*others.choose(rng).unwrap())
+}
+
+fn port_cargos(model: &Model) -> &Cargos {
+    &model
+        .ports
+        .get(&model.current_port_location)
+        .unwrap()
+        .cargos
+}
+
+fn has_cargo_space(model: &Model) -> bool {
+    let ship = &model.player.ship;
+    ship.cargos.total_unit() < ship.cargos_capacity
+}
+
+fn needs_food(model: &Model) -> Option<Msg> {
+    let l = model.current_port_location;
+    let missing = FOOD_RESERVE - model.player.ship.cargos.unit(CargoKind::Food);
+    let units = missing.min(market::max_buy(model, l, CargoKind::Food, Venue::Market));
+
+    if units > 0 {
+        Some(Msg::BuyCargo(l, CargoKind::Food, units))
+    } else {
+        None
+    }
+}
+
+// Sells goods above their base price, buys them well below it, and sails on.
+pub struct GreedyTrader;
+
+impl Strategy for GreedyTrader {
+    fn next_msg(&mut self, model: &Model, rng: &mut ChaCha8Rng) -> Msg {
+        if let Some(msg) = needs_food(model) {
+            return msg;
+        }
+
+        let l = model.current_port_location;
+        let port = port_cargos(model);
+        let quote = |kind| market::quote(model, l, kind);
+        let held = &model.player.ship.cargos;
+        // Stays clear of anything some nation calls contraband.
+        let is_trade_good = |kind: &CargoKind| {
+            *kind != CargoKind::Food && CONTENT.cargos[kind].banned_by.is_empty()
+        };
+
+        let to_sell = CARGO_KINDS
+            .iter()
+            .filter(|k| is_trade_good(k))
+            .find(|&&kind| {
+                held.unit(kind) > 0 && quote(kind).sell > CONTENT.cargos[&kind].base_price
+            });
+        if let Some(&kind) = to_sell {
+            return Msg::SellCargo(l, kind, 1);
+        }
+
+        let to_buy = CARGO_KINDS
+            .iter()
+            .filter(|k| is_trade_good(k))
+            .filter(|&&kind| {
+                let price = quote(kind).buy;
+                port.unit(kind) > 0
+                    && price * 5 < CONTENT.cargos[&kind].base_price * 4
+                    && model.player.coins >= price
+            })
+            .min_by_key(|&&kind| quote(kind).buy * 100 / CONTENT.cargos[&kind].base_price);
+        if let (Some(&kind), true) = (to_buy, has_cargo_space(model)) {
+            return Msg::BuyCargo(l, kind, 1);
+        }
+
+        sail_anywhere(model, rng)
+    }
+}
+
+// Fights a skirmish, takes what it can carry, sells it and sails on.
+#[derive(Default)]
+pub struct Pirate {
+    fought: bool,
+}
+
+impl Strategy for Pirate {
+    fn next_msg(&mut self, model: &Model, rng: &mut ChaCha8Rng) -> Msg {
+        let ship = &model.player.ship;
+        let coins = model.player.coins;
+
+        match &model.current_screen {
+            Screen::Skirmish => return Msg::SwitchScreen(Screen::SkirmishChase),
+            Screen::SkirmishChase => return Msg::SkirmishChaseClose,
+            Screen::SkirmishBattle => {
+                self.fought = true;
+                return match &model.enemy {
+                    Some(enemy) if enemy.ship.crew >= MINIMUM_SHIP_CREW.into() => {
+                        Msg::SkirmishBattleSwingSword
+                    }
+                    Some(_) => Msg::SwitchScreen(Screen::SkirmishLoot),
+                    None => Msg::SwitchScreen(Screen::MainNavigation),
+                };
+            }
+            Screen::SkirmishLoot => {
+                let loot = model.enemy.as_ref().and_then(|enemy| {
+                    CARGO_KINDS
+                        .iter()
+                        .find(|&&kind| enemy.ship.cargos.unit(kind) > 0)
+                });
+                return match loot {
+                    Some(&kind) if has_cargo_space(model) => Msg::TakeEnemyCargo(kind),
+                    _ => Msg::SwitchScreen(Screen::MainNavigation),
+                };
+            }
+            _ => (),
+        }
+
+        // Contraband waits in the hold for a port with a black market.
+        let l = model.current_port_location;
+        let port = &model.ports[&l];
+        let to_sell = CARGO_KINDS
+            .iter()
+            .filter(|&&kind| kind != CargoKind::Food && ship.cargos.unit(kind) > 0)
+            .find_map(|&kind| {
+                let units = ship.cargos.unit(kind);
+                match (
+                    customs::is_contraband(&CONTENT, port, kind),
+                    port.black_market,
+                ) {
+                    (false, _) => Some(Msg::SellCargo(l, kind, units)),
+                    (true, true) => Some(Msg::SellContraband(l, kind, units)),
+                    (true, false) => None,
+                }
+            });
+        if let Some(msg) = to_sell {
+            return msg;
+        }
+        if let Some(msg) = needs_food(model) {
+            return msg;
+        }
+        if ship.hull < ship.hull_capacity && coins >= ship.cost_to_repair() {
+            return Msg::RepairShip(coins);
+        }
+        if ship.crew < ship.crew_capacity && coins >= ship.cost_to_hire() {
+            return Msg::HireCrew(coins);
+        }
+
+        if self.fought {
+            self.fought = false;
+            sail_anywhere(model, rng)
+        } else {
+            Msg::SwitchScreen(Screen::Skirmish)
+        }
+    }
+}
+
+// Clicks around like someone who has not read the rules.
+pub struct RandomPlayer;
+
+impl Strategy for RandomPlayer {
+    fn next_msg(&mut self, model: &Model, rng: &mut ChaCha8Rng) -> Msg {
+        let l = model.current_port_location;
+        let kind = *CARGO_KINDS.choose(rng).unwrap();
+        let units = rng.gen_range(1..=20);
+
+        let msgs = match &model.current_screen {
+            Screen::Skirmish => vec![
+                Msg::SwitchScreen(Screen::SkirmishChase),
+                Msg::SwitchScreen(Screen::MainNavigation),
+            ],
+            Screen::SkirmishChase => vec![
+                Msg::SkirmishChaseClose,
+                Msg::SkirmishChaseDistant,
+                Msg::SkirmishChaseBroadside,
+            ],
+            Screen::SkirmishBattle => match &model.enemy {
+                Some(enemy) if enemy.ship.crew >= MINIMUM_SHIP_CREW.into() => vec![
+                    Msg::SkirmishBattleSwingSword,
+                    Msg::SkirmishBattleShootFalconet,
+                ],
+                _ => vec![Msg::SwitchScreen(Screen::SkirmishLoot)],
+            },
+            Screen::SkirmishLoot => vec![
+                Msg::TakeEnemyCargo(kind),
+                Msg::SwitchScreen(Screen::MainNavigation),
+            ],
+            _ => vec![
+                Msg::BuyCargo(l, kind, units),
+                Msg::SellCargo(l, kind, units),
+                Msg::BuyContraband(l, kind, units),
+                Msg::SellContraband(l, kind, units),
+                Msg::RepairShip(model.player.coins),
+                Msg::HireCrew(model.player.coins),
+                Msg::TakeLoan(rng.gen_range(1..=5_000)),
+                Msg::RepayLoan(model.player.coins),
+                Msg::SwitchScreen(Screen::Skirmish),
+                sail_anywhere(model, rng),
+            ],
+        };
+
+        msgs[rng.gen_range(0..msgs.len())].to_owned()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct GameResult {
+    pub lost: Option<GameLostReason>,
+    pub days: i64,
+    pub coins: i32,
+    // Coins at every checkpoint the game lived to see.
+    pub checkpoint_coins: Vec<i32>,
+    pub refused_actions: u32,
+}
+
+pub fn play(new_strategy: NewStrategy, seed: u64, max_actions: u32) -> GameResult {
+    let mut model = Model::default();
+    model.reseed(seed);
+    model.current_screen = Screen::MainNavigation;
+    let start = model.date;
+
+    let mut strategy = new_strategy();
+    // Kept apart from the game rng, so a bot's choices don't shift the rolls.
+    let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(1));
+    let mut checkpoint_coins = Vec::new();
+    let mut refused_actions = 0;
+    // Taken before every action, since losing resets the date and the coins.
+    let mut days = 0;
+    let mut coins = model.player.coins;
+
+    for _ in 0..max_actions {
+        let msg = strategy.next_msg(&model, &mut rng);
+        if engine::apply(&mut model, msg).is_err() {
+            refused_actions += 1;
+        }
+
+        if let Screen::GameLost(reason) = model.current_screen {
+            return GameResult {
+                lost: Some(reason),
+                days,
+                coins,
+                checkpoint_coins,
+                refused_actions,
+            };
+        }
+
+        days = (model.date - start).num_days();
+        coins = model.player.coins;
+        while checkpoint_coins.len() < CHECKPOINT_DAYS.len()
+            && days >= CHECKPOINT_DAYS[checkpoint_coins.len()]
+        {
+            checkpoint_coins.push(model.player.coins);
+        }
+    }
+
+    GameResult {
+        lost: None,
+        days,
+        coins,
+        checkpoint_coins,
+        refused_actions,
+    }
+}
+
+// Minimum, 10th percentile, median, 90th percentile and maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Distribution {
+    pub samples: usize,
+    pub min: i64,
+    pub p10: i64,
+    pub median: i64,
+    pub p90: i64,
+    pub max: i64,
+}
+
+impl Distribution {
+    pub fn of(mut values: Vec<i64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        values.sort_unstable();
+        let at = |p: usize| values[(values.len() - 1) * p / 100];
+        Some(Self {
+            samples: values.len(),
+            min: at(0),
+            p10: at(10),
+            median: at(50),
+            p90: at(90),
+            max: at(100),
+        })
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {} / p10 {} / median {} / p90 {} / max {} ({} games)",
+            self.min, self.p10, self.median, self.p90, self.max, self.samples
+        )
+    }
+}
+
+pub struct Report {
+    pub strategy: String,
+    pub games: usize,
+    pub max_actions: u32,
+    pub results: Vec<GameResult>,
+}
+
+impl Report {
+    pub fn lost_reasons(&self) -> BTreeMap<String, usize> {
+        let mut reasons = BTreeMap::new();
+        for reason in self.results.iter().filter_map(|r| r.lost) {
+            *reasons.entry(format!("{:?}", reason)).or_insert(0) += 1;
+        }
+        reasons
+    }
+
+    pub fn days_survived(&self) -> Option<Distribution> {
+        Distribution::of(self.results.iter().map(|r| r.days).collect())
+    }
+
+    pub fn final_coins(&self) -> Option<Distribution> {
+        Distribution::of(self.results.iter().map(|r| r.coins.into()).collect())
+    }
+
+    pub fn coins_at_checkpoint(&self, i: usize) -> Option<Distribution> {
+        Distribution::of(
+            self.results
+                .iter()
+                .filter_map(|r| r.checkpoint_coins.get(i).map(|&c| c.into()))
+                .collect(),
+        )
+    }
+}
+
+fn write_distribution(
+    f: &mut fmt::Formatter<'_>,
+    label: &str,
+    distribution: Option<Distribution>,
+) -> fmt::Result {
+    match distribution {
+        Some(d) => writeln!(f, "{}: {}", label, d),
+        None => writeln!(f, "{}: no games", label),
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Strategy {}: {} games of up to {} actions",
+            self.strategy, self.games, self.max_actions
+        )?;
+
+        let lost = self.results.iter().filter(|r| r.lost.is_some()).count();
+        writeln!(f, "Games lost: {} of {}", lost, self.games)?;
+        for (reason, count) in self.lost_reasons() {
+            writeln!(f, "  {}: {}", reason, count)?;
+        }
+
+        write_distribution(f, "Days survived", self.days_survived())?;
+        for (i, day) in CHECKPOINT_DAYS.iter().enumerate() {
+            write_distribution(
+                f,
+                &format!("Coins on day {}", day),
+                self.coins_at_checkpoint(i),
+            )?;
+        }
+        write_distribution(f, "Final coins", self.final_coins())?;
+
+        let refused: u32 = self.results.iter().map(|r| r.refused_actions).sum();
+        writeln!(f, "Refused actions: {}", refused)
+    }
+}
+
+// Game `i` is played with seed `seed + i`, so a report can be reproduced.
+pub fn simulate(
+    strategy: &str,
+    new_strategy: NewStrategy,
+    games: usize,
+    seed: u64,
+    max_actions: u32,
+) -> Report {
+    Report {
+        strategy: strategy.to_string(),
+        games,
+        max_actions,
+        results: (0..games)
+            .map(|i| play(new_strategy, seed.wrapping_add(i as u64), max_actions))
+            .collect(),
+    }
+}
@@ -0,0 +1,92 @@
+//! Docking fees and tariffs a port levies on the player, depending on how the
+//! player's nation stands with the port's.
+
+use strum_macros::EnumIter;
+
+use crate::market::Venue;
+use crate::model::*;
+use Nationality::*;
+use Relation::*;
+
+#[derive(Debug, EnumIter, Copy, Clone, PartialEq, Eq)]
+pub enum Relation {
+    Home,
+    Allied,
+    Neutral,
+    Hostile,
+}
+
+// How the nations stand with each other. Pairs missing here are neutral, and
+// relations do not change during a game yet.
+const RELATIONS: [(Nationality, Nationality, Relation); 3] = [
+    (British, French, Allied),
+    (British, Spanish, Hostile),
+    (French, Spanish, Hostile),
+];
+
+pub fn relation(a: &Nationality, b: &Nationality) -> Relation {
+    if a == b {
+        return Home;
+    }
+    RELATIONS
+        .iter()
+        .find(|(x, y, _)| (x == a && y == b) || (x == b && y == a))
+        .map_or(Neutral, |(_, _, relation)| *relation)
+}
+
+// What a port charges the player. Tariffs are in percent of the goods' price:
+// imports are goods the player sells to the port, exports the ones bought.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Levies {
+    pub docking_fee: i32,
+    pub import_percent: i32,
+    pub export_percent: i32,
+}
+
+pub fn levies(port: &Port, player: &Player) -> Levies {
+    let fee = match port.population {
+        PortPopulation::Small => 10,
+        PortPopulation::Medium => 20,
+        PortPopulation::Large => 30,
+        PortPopulation::Huge => 40,
+    };
+    let (fee_percent, import_percent, export_percent) =
+        match relation(&player.nationality, &port.nationality) {
+            Home => (50, 0, 0),
+            Allied => (100, 5, 0),
+            Neutral => (150, 10, 5),
+            Hostile => (300, 20, 10),
+        };
+
+    Levies {
+        docking_fee: fee * fee_percent / 100,
+        import_percent,
+        export_percent,
+    }
+}
+
+// Tariff on goods worth `amount`, rounded up.
+pub fn duty(amount: i32, percent: i32) -> i32 {
+    (amount * percent + 99) / 100
+}
+
+// Smugglers on the black market pay no tariffs.
+pub fn export_duty(model: &Model, location: PortLocation, venue: Venue, amount: i32) -> i32 {
+    match venue {
+        Venue::Market => duty(
+            amount,
+            levies(&model.ports[&location], &model.player).export_percent,
+        ),
+        Venue::BlackMarket => 0,
+    }
+}
+
+pub fn import_duty(model: &Model, location: PortLocation, venue: Venue, amount: i32) -> i32 {
+    match venue {
+        Venue::Market => duty(
+            amount,
+            levies(&model.ports[&location], &model.player).import_percent,
+        ),
+        Venue::BlackMarket => 0,
+    }
+}
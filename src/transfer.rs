@@ -0,0 +1,65 @@
+//! Export and import of a whole game, either as a JSON file or as a compact
+//! text code that can be pasted around.
+
+use std::fmt;
+
+use crate::i18n::{self, Locale};
+use crate::invariants;
+use crate::model::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    Decode(String),
+    Parse(String),
+    Invalid(String),
+}
+
+impl ImportError {
+    // What the player is told, translated when shown.
+    pub fn text(&self) -> Text {
+        match self {
+            Self::Decode(reason) => Text::new("import.cannot_decode").arg("reason", reason),
+            Self::Parse(reason) => Text::new("import.cannot_read").arg("reason", reason),
+            Self::Invalid(reason) => Text::new("saves.invalid").arg("reason", reason),
+        }
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", i18n::render(Locale::English, &self.text()))
+    }
+}
+
+pub fn export_json(model: &Model) -> String {
+    serde_json::to_string_pretty(model).unwrap()
+}
+
+pub fn export_code(model: &Model) -> String {
+    base64::encode_config(
+        serde_json::to_string(model).unwrap(),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+pub fn validate(model: &Model) -> Result<(), ImportError> {
+    invariants::check(model).map_err(|v| ImportError::Invalid(v.to_string()))
+}
+
+// Accepts both the JSON file and the text code produced by the exports.
+pub fn import(text: &str) -> Result<Model, ImportError> {
+    let text = text.trim();
+    let json = if text.starts_with('{') {
+        text.to_string()
+    } else {
+        let bytes = base64::decode_config(text, base64::URL_SAFE_NO_PAD)
+            .map_err(|e| ImportError::Decode(e.to_string()))?;
+        String::from_utf8(bytes).map_err(|e| ImportError::Decode(e.to_string()))?
+    };
+
+    let model =
+        serde_json::from_str::<Model>(&json).map_err(|e| ImportError::Parse(e.to_string()))?;
+    validate(&model)?;
+
+    Ok(model)
+}
@@ -0,0 +1,126 @@
+//! Which screen `Msg::SwitchScreen` may lead to from which, and on what
+//! condition.
+//!
+//! Moves the rules make on their own, like boarding the enemy or losing the
+//! game, set the screen directly and are not listed here.
+
+use crate::engine::GameError;
+use crate::model::*;
+use Guard::*;
+use Screen::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Guard {
+    Always,
+    // The enemy crew is too few to fight back.
+    EnemyDefeated,
+    // The player is docked at a port with a black market.
+    BlackMarket,
+}
+
+pub const SCREENS: [Screen; 22] = [
+    Screen::MainNavigation,
+    Screen::About,
+    Screen::MainMenu,
+    Screen::SaveSlots,
+    Screen::ImportSave,
+    Screen::NewCharacter,
+    Screen::Profile,
+    Screen::Dock,
+    Screen::DockTavern,
+    Screen::TavernHireCrew,
+    Screen::TavernMoneylender,
+    Screen::DockMarket,
+    Screen::DockBlackMarket,
+    Screen::DockShipyard,
+    Screen::Skirmish,
+    Screen::SkirmishChase,
+    Screen::SkirmishBattle,
+    Screen::SkirmishLoot,
+    Screen::GameLost(GameLostReason::ShipSunk),
+    Screen::GameLost(GameLostReason::AllCrewDied),
+    Screen::GameLost(GameLostReason::FoodMutiny),
+    Screen::GameLost(GameLostReason::Bankruptcy),
+];
+
+pub const TRANSITIONS: [(Screen, Screen, Guard); 48] = [
+    (MainMenu, About, Always),
+    (MainMenu, SaveSlots, Always),
+    (MainMenu, ImportSave, Always),
+    (MainMenu, NewCharacter, Always),
+    (MainMenu, MainNavigation, Always),
+    (About, MainMenu, Always),
+    (SaveSlots, MainMenu, Always),
+    (SaveSlots, NewCharacter, Always),
+    (SaveSlots, MainNavigation, Always),
+    (ImportSave, MainMenu, Always),
+    (ImportSave, MainNavigation, Always),
+    (NewCharacter, MainMenu, Always),
+    (NewCharacter, MainNavigation, Always),
+    (MainNavigation, MainMenu, Always),
+    (MainNavigation, Profile, Always),
+    (MainNavigation, Dock, Always),
+    (MainNavigation, Skirmish, Always),
+    (Profile, MainNavigation, Always),
+    (Dock, MainNavigation, Always),
+    (Dock, DockTavern, Always),
+    (Dock, DockMarket, Always),
+    (Dock, DockBlackMarket, BlackMarket),
+    (Dock, DockShipyard, Always),
+    (DockTavern, MainNavigation, Always),
+    (DockTavern, Dock, Always),
+    (DockTavern, TavernHireCrew, Always),
+    (TavernHireCrew, MainNavigation, Always),
+    (TavernHireCrew, Dock, Always),
+    (TavernHireCrew, DockTavern, Always),
+    (DockTavern, TavernMoneylender, Always),
+    (TavernMoneylender, MainNavigation, Always),
+    (TavernMoneylender, Dock, Always),
+    (TavernMoneylender, DockTavern, Always),
+    (DockMarket, MainNavigation, Always),
+    (DockMarket, Dock, Always),
+    (DockBlackMarket, MainNavigation, Always),
+    (DockBlackMarket, Dock, Always),
+    (DockShipyard, MainNavigation, Always),
+    (DockShipyard, Dock, Always),
+    // The enemy is only sighted, so sailing away costs nothing yet.
+    (Skirmish, SkirmishChase, Always),
+    (Skirmish, MainNavigation, Always),
+    (SkirmishBattle, SkirmishLoot, EnemyDefeated),
+    (SkirmishBattle, MainNavigation, EnemyDefeated),
+    (SkirmishLoot, MainNavigation, Always),
+    (GameLost(GameLostReason::ShipSunk), MainMenu, Always),
+    (GameLost(GameLostReason::AllCrewDied), MainMenu, Always),
+    (GameLost(GameLostReason::FoodMutiny), MainMenu, Always),
+    (GameLost(GameLostReason::Bankruptcy), MainMenu, Always),
+];
+
+pub fn guard_of(from: &Screen, to: &Screen) -> Option<Guard> {
+    TRANSITIONS
+        .iter()
+        .find(|(f, t, _)| f == from && t == to)
+        .map(|(_, _, guard)| *guard)
+}
+
+pub fn check(model: &Model, to: &Screen) -> Result<(), GameError> {
+    let from = &model.current_screen;
+    let unreachable = || GameError::ScreenUnreachable {
+        from: from.clone(),
+        to: to.clone(),
+    };
+
+    match guard_of(from, to).ok_or_else(unreachable)? {
+        Always => Ok(()),
+        EnemyDefeated => match &model.enemy {
+            Some(enemy) if enemy.ship.crew >= MINIMUM_SHIP_CREW.into() => {
+                Err(GameError::EnemyNotDefeated)
+            }
+            Some(_) => Ok(()),
+            None => Err(GameError::NotInCombat),
+        },
+        BlackMarket => match model.ports.get(&model.current_port_location) {
+            Some(port) if port.black_market => Ok(()),
+            _ => Err(GameError::NoBlackMarket(model.current_port_location)),
+        },
+    }
+}
@@ -1,13 +1,57 @@
+use std::collections::VecDeque;
 use std::rc::Rc;
 use std::str::FromStr;
 use ternop::ternary;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{window, HtmlElement, HtmlInputElement};
+use web_sys::{
+    window, Blob, FileReader, HtmlAnchorElement, HtmlInputElement, HtmlSelectElement,
+    HtmlTextAreaElement, Url,
+};
 use yew::prelude::*;
 use yewdux::prelude::*;
 
-use crate::model::*;
+use crate::recovery::Recovery;
+use crate::routes;
+use lunar::customs::is_contraband;
+use lunar::i18n::{self, tr, tr_with, Locale, Named, LOCALES};
+use lunar::journal::{replay, Journal};
+use lunar::lending;
+use lunar::market::{buy_cost, max_buy, quote_at, sell_proceeds, Venue};
+use lunar::model::*;
+use lunar::saves::{now, BrokenSave, SaveSlot, Saves};
+use lunar::settings::Settings;
+use lunar::tariffs::{self, export_duty, import_duty};
+use lunar::transfer::{export_code, export_json, import};
+use lunar::web::{load, LocalStorage};
+
+fn locale() -> Locale {
+    Dispatch::<Settings>::new().get().locale
+}
+
+// Text for `key` in the player's language.
+fn t(key: &str) -> String {
+    tr(locale(), key)
+}
+
+fn t_with(key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    tr_with(locale(), key, args)
+}
+
+// Let the browser save `contents` as a file named `file_name`.
+fn download_file(file_name: &str, contents: &str) {
+    let document = window().unwrap().document().unwrap();
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let blob = Blob::new_with_str_sequence(&parts).unwrap();
+    let url = Url::create_object_url_with_blob(&blob).unwrap();
+
+    let anchor: HtmlAnchorElement = document.create_element("a").unwrap().unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    Url::revoke_object_url(&url).unwrap();
+}
 
 fn root_container(view: Html) -> Html {
     html! {
@@ -23,10 +67,117 @@ fn root_container(view: Html) -> Html {
     }
 }
 
-fn onclick_styled_btn(callback: Callback<MouseEvent>, btn_text: &str) -> Html {
+pub(crate) fn onclick_styled_btn(callback: Callback<MouseEvent>, btn_text: &str) -> Html {
     html! { <button class="button" onclick={callback}>{btn_text}</button> }
 }
 
+// Ask the player first, and only apply the message once confirmed.
+fn onclick_confirmed_btn(
+    dispatch: &Dispatch<Model>,
+    msg: Msg,
+    question: Text,
+    btn_text: &str,
+) -> Html {
+    let callback = dispatch.apply_callback(move |_| {
+        Msg::AskConfirmation(question.to_owned(), Box::new(msg.to_owned()))
+    });
+
+    onclick_styled_btn(callback, btn_text)
+}
+
+fn severity_class(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "is-info",
+        Severity::Success => "is-success",
+        Severity::Warning => "is-warning",
+        Severity::Danger => "is-danger",
+    }
+}
+
+// What the last action added to the log, fading away after a few seconds.
+fn show_toasts(model: &Model, dispatch: &Dispatch<Model>) -> Html {
+    html! {
+        <div class="toasts">
+            { for model.toasts().iter().map(|entry| html! {
+                <div key={entry.id} class={classes!("notification", "toast", severity_class(entry.severity))}>
+                    <button class="delete" onclick={dispatch.apply_callback(|_| Msg::DismissToasts)}></button>
+                    { i18n::render(locale(), &entry.text) }
+                </div>
+            }) }
+        </div>
+    }
+}
+
+fn show_captains_log(model: &Model) -> Html {
+    html! {
+        <div class="box">
+            <h2>{t("log.title")}</h2>
+            <hr/>
+
+            <div class="captains-log">
+                { if model.log.is_empty() {
+                    html! { <p>{t("log.empty")}</p> }
+                } else {
+                    model.log.iter().rev().map(|entry| html! {
+                        <p>
+                            <span class={classes!("tag", severity_class(entry.severity))}>{entry.date}</span>
+                            {" "} {i18n::render(locale(), &entry.text)}
+                        </p>
+                    }).collect::<Html>()
+                }}
+            </div>
+        </div>
+    }
+}
+
+fn confirmation_modal(
+    question: &Text,
+    on_confirm: Callback<MouseEvent>,
+    on_cancel: Callback<MouseEvent>,
+) -> Html {
+    html! {
+        <div class="modal is-active">
+            <div class="modal-background" onclick={on_cancel.clone()}></div>
+            <div class="modal-card">
+                <section class="modal-card-body">
+                    <p>{i18n::render(locale(), question)}</p>
+                </section>
+                <footer class="modal-card-foot">
+                    <button class="button is-success" onclick={on_confirm}>{t("confirm.confirm")}</button>
+                    <button class="button" onclick={on_cancel}>{t("confirm.cancel")}</button>
+                </footer>
+            </div>
+        </div>
+    }
+}
+
+fn show_confirmation_modal(model: &Model, dispatch: &Dispatch<Model>) -> Html {
+    match &model.pending_confirmation {
+        Some(confirmation) => confirmation_modal(
+            &confirmation.question,
+            dispatch.apply_callback(|_| Msg::Confirm),
+            dispatch.apply_callback(|_| Msg::CancelConfirmation),
+        ),
+        None => html!(),
+    }
+}
+
+// Save slots live outside the game, so deleting one is confirmed in the same
+// modal without going through the engine or the journal.
+fn show_deletion_modal(saves: &Saves) -> Html {
+    match saves.pending_deletion.and_then(|id| saves.get(id)) {
+        Some(slot) => {
+            let dispatch = Dispatch::<Saves>::new();
+            confirmation_modal(
+                &Text::new("saves.delete_question").arg("name", &slot.name),
+                dispatch.reduce_mut_callback(|s| s.confirm_deletion(&mut LocalStorage)),
+                dispatch.reduce_mut_callback(|s| s.cancel_deletion()),
+            )
+        }
+        None => html!(),
+    }
+}
+
 fn onclick_switch_screen(dispatch: &Dispatch<Model>, screen: Screen, name: &str) -> Html {
     html! {
         { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SwitchScreen(screen.to_owned())), name) }
@@ -47,38 +198,101 @@ fn link_switch_location(dispatch: &Dispatch<Model>, location: PortLocation, name
     }
 }
 
-// TODO: Find a way to make it work with cfg! and debug_assertions.
-fn debug_header(dispatch: &Dispatch<Model>) -> Html {
+fn broken_save_item(broken: &BrokenSave) -> Html {
+    let file_name = format!(
+        "lunar-{}.json",
+        broken.label.to_lowercase().replace(' ', "-")
+    );
+    let raw = broken.raw.clone();
+
+    html! {
+        <div class="box">
+            <h4 class="title is-4">{&broken.label}</h4>
+            <p>{&broken.error}</p>
+            <br/>
+            { onclick_styled_btn(Callback::from(move |_| download_file(file_name.as_str(), raw.as_str())), &t("recovery.download")) }
+        </div>
+    }
+}
+
+fn show_recovery(recovery: &Recovery) -> Html {
     html! {
         <div>
-            <h1>{"Lunar"}</h1>
-            { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::ResetModel), "!!!RESET MODEL!!!")}
-            { onclick_switch_screen(dispatch, Screen::MainMenu, "Back to main menu") }
+            <h3 class="title is-3">{t("recovery.title")}</h3>
+            <p>{t("recovery.explanation")}</p>
             <hr/>
+
+            { recovery.broken.iter().map(broken_save_item).collect::<Html>() }
+
+            { onclick_styled_btn(Dispatch::<Recovery>::new().set_callback(|_| Recovery::default()), &t("recovery.start_over")) }
         </div>
     }
 }
 
-fn show_about(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
+fn show_about(_model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
     html! {
         <div>
-            <a target="_blank" href="https://github.com/modotte/lunar">{"Source code on Github"}</a>
-            <p>{"Licensed under the GPL-3.0-or-later license"}</p>
-            { onclick_switch_screen(dispatch, Screen::MainMenu, "Back") }
+            <a target="_blank" href="https://github.com/modotte/lunar">{t("about.source")}</a>
+            <p>{t("about.license")}</p>
+            { onclick_switch_screen(dispatch, Screen::MainMenu, &t("common.back")) }
         </div>
     }
 }
 
-fn show_main_menu(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
+fn locale_select() -> Html {
+    let onchange = Dispatch::<Settings>::new().reduce_mut_callback_with(|settings, e: Event| {
+        let select: HtmlSelectElement = e.target_unchecked_into();
+        if let Some(l) = LOCALES.get(select.selected_index().max(0) as usize) {
+            settings.locale = *l;
+        }
+    });
+
+    html! {
+        <div class="select is-small">
+            <select aria-label={t("menu.language")} {onchange}>
+                { for LOCALES.iter().map(|l| html! {
+                    <option selected={*l == locale()}>{l.name(*l)}</option>
+                }) }
+            </select>
+        </div>
+    }
+}
+
+// Start over from a fresh model. The previous game stays in its save slot.
+fn onclick_new_game(dispatch: &Dispatch<Model>, btn_text: &str) -> Html {
+    let dispatch = dispatch.clone();
+    let callback = Callback::from(move |_| {
+        dispatch.apply(Msg::ResetModel);
+        dispatch.apply(Msg::SwitchScreen(Screen::NewCharacter));
+    });
+
+    onclick_styled_btn(callback, btn_text)
+}
+
+fn show_main_menu(_model: Rc<Model>, dispatch: &Dispatch<Model>, saves: &Saves) -> Html {
     html! {
         <div class="tile is-ancestor main-menu">
             <div class="tile is-vertical is-8">
                 <div class="tile is-parent is-vertical">
+                    { ternary!(saves.active_slot().is_some(), html! {
+                        <div class="tile">
+                            { onclick_switch_screen(dispatch, Screen::MainNavigation, &t("common.continue")) }
+                        </div>
+                    }, html!()) }
+                    <div class="tile">
+                        { onclick_new_game(dispatch, &t("menu.start")) }
+                    </div>
+                    <div class="tile">
+                        { onclick_switch_screen(dispatch, Screen::SaveSlots, &t("menu.load")) }
+                    </div>
                     <div class="tile">
-                        { onclick_switch_screen(dispatch, Screen::NewCharacter, "Start") }
+                        { onclick_switch_screen(dispatch, Screen::ImportSave, &t("menu.import")) }
                     </div>
                     <div class="tile">
-                        { onclick_switch_screen(dispatch, Screen::About, "About") }
+                        { onclick_switch_screen(dispatch, Screen::About, &t("menu.about")) }
+                    </div>
+                    <div class="tile">
+                        { locale_select() }
                     </div>
                 </div>
             </div>
@@ -86,61 +300,192 @@ fn show_main_menu(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
     }
 }
 
+fn save_slot_item(dispatch: &Dispatch<Model>, slot: &SaveSlot) -> Html {
+    let id = slot.id;
+    let saves_dispatch = Dispatch::<Saves>::new();
+
+    let load = {
+        let dispatch = dispatch.clone();
+        let slot = slot.clone();
+        Callback::from(move |_| match load(slot.model.clone()) {
+            Ok(()) => {
+                Dispatch::<Saves>::new()
+                    .reduce_mut(|s| s.activate(&mut LocalStorage, Some(slot.id)));
+                dispatch.apply(Msg::SwitchScreen(Screen::MainNavigation));
+            }
+            Err(violation) => dispatch.apply(Msg::Notify(Severity::Danger, violation.text())),
+        })
+    };
+    html! {
+        <div class="box">
+            <input class="input is-small" type="text" value={slot.name.to_string()}
+                onchange={saves_dispatch.reduce_mut_callback_with(move |saves, e: Event| {
+                    let input: HtmlInputElement = e.target_unchecked_into();
+
+                    saves.rename(&mut LocalStorage, id, input.value());
+                })}
+            />
+            <ul>
+                <li>{t("saves.captain")} {": "} {slot.captain()}</li>
+                <li>{t("saves.ship")} {": "} {&slot.ship().name} {" ("} {slot.ship().class.name(locale())} {")"}</li>
+                <li>{t("common.date")} {": "} {slot.date()}</li>
+                <li>{t("common.coins")} {": "} {slot.coins()}</li>
+                <li>{t("saves.last_played")} {": "} {slot.last_played.format("%Y-%m-%d %H:%M")}</li>
+            </ul>
+            { onclick_styled_btn(load, &t("saves.load")) }
+            { onclick_styled_btn(saves_dispatch.reduce_mut_callback(move |saves| { saves.duplicate(&mut LocalStorage, id, now()); }), &t("saves.duplicate")) }
+            { onclick_styled_btn(saves_dispatch.reduce_mut_callback(move |saves| saves.ask_deletion(id)), &t("saves.delete")) }
+        </div>
+    }
+}
+
+fn show_save_slots(_model: Rc<Model>, dispatch: &Dispatch<Model>, saves: &Saves) -> Html {
+    html! {
+        <div>
+            <h3 class="title is-3">{t("saves.title")}</h3>
+            <hr/>
+
+            { if saves.slots.is_empty() {
+                html! { <p>{t("saves.empty")}</p> }
+            } else {
+                saves.sorted().into_iter().map(|slot| save_slot_item(dispatch, slot)).collect::<Html>()
+            }}
+
+            <br/>
+            { onclick_new_game(dispatch, &t("saves.new_game")) }
+            { onclick_switch_screen(dispatch, Screen::MainMenu, &t("common.back")) }
+        </div>
+    }
+}
+
+// Play an imported game from its own new save slot.
+fn start_imported_game(dispatch: &Dispatch<Model>, text: &str) {
+    match import(text) {
+        Ok(model) => {
+            let name = t_with("saves.imported_voyage", &[("captain", &model.player.name)]);
+            if let Err(violation) = load(model) {
+                dispatch.apply(Msg::Notify(Severity::Danger, violation.text()));
+                return;
+            }
+            let journal = Dispatch::<Journal>::new().get();
+            Dispatch::<Saves>::new().reduce_mut(|s| {
+                s.create(&mut LocalStorage, name, &dispatch.get(), &journal, now());
+            });
+            dispatch.apply(Msg::SwitchScreen(Screen::MainNavigation));
+        }
+        Err(e) => dispatch.apply(Msg::Notify(Severity::Danger, e.text())),
+    }
+}
+
+#[function_component]
+fn ImportSave() -> Html {
+    let dispatch = Dispatch::<Model>::new();
+    let code = use_state(String::new);
+    // Only to render again when the language changes.
+    use_store_value::<Settings>();
+
+    let oninput = {
+        let code = code.clone();
+        Callback::from(move |e: InputEvent| {
+            code.set(e.target_unchecked_into::<HtmlTextAreaElement>().value())
+        })
+    };
+    let onclick = {
+        let dispatch = dispatch.clone();
+        let code = code.clone();
+        Callback::from(move |_| start_imported_game(&dispatch, code.as_str()))
+    };
+    let onchange = {
+        let dispatch = dispatch.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                let reader = FileReader::new().unwrap();
+                let onload = {
+                    let reader = reader.clone();
+                    let dispatch = dispatch.clone();
+                    Closure::once_into_js(move || {
+                        if let Some(text) = reader.result().ok().and_then(|r| r.as_string()) {
+                            start_imported_game(&dispatch, text.as_str());
+                        }
+                    })
+                };
+                reader.set_onload(Some(onload.unchecked_ref()));
+                reader.read_as_text(&file).unwrap();
+            }
+        })
+    };
+
+    html! {
+        <div>
+            <h3 class="title is-3">{t("import.title")}</h3>
+            <hr/>
+
+            <label>{t("import.file")}</label>
+            <input class="input is-small" type="file" accept=".json,application/json" {onchange} />
+
+            <br/>
+            <label>{t("common.save_code")}</label>
+            <textarea class="textarea is-small" placeholder={t("import.placeholder")} value={(*code).clone()} {oninput} />
+
+            <br/>
+            { onclick_styled_btn(onclick, &t("import.import")) }
+            { onclick_switch_screen(&dispatch, Screen::MainMenu, &t("common.back")) }
+        </div>
+    }
+}
+
 fn show_new_character(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
     let player = &model.player;
     html! {
         <div>
-        { debug_header(dispatch) }
 
         <div>
-            <h3 class="title is-3">{"Create a new character!"}</h3>
+            <h3 class="title is-3">{t("character.title")}</h3>
         </div>
 
         <hr/>
 
-        <label>{"Name"}</label>
+        <label>{t("character.name")}</label>
         <br/>
         <input class="input is-small" placeholder="Player" required=true type="text" value={player.name.to_string()}
-            onchange={dispatch.reduce_mut_callback_with(move |model, e: Event| {
+            onchange={dispatch.apply_callback(move |e: Event| {
                 let input: HtmlInputElement = e.target_unchecked_into();
 
-                model.player.name = input.value();
+                Msg::SetPlayerName(input.value())
             })}
         />
 
         <br/>
-        <label>{"Age"}</label>
+        <label>{t("character.age")}</label>
         <br/>
         <input class="input is-small" placeholder={MINIMUM_PLAYER_AGE.to_string()} required=true type="number" min={MINIMUM_PLAYER_AGE.to_string()} max={MAXIMUM_PLAYER_AGE.to_string()} value={player.age.to_string()}
-        onchange={dispatch.reduce_mut_callback_with(move |model, e: Event| {
-            let input: HtmlInputElement = e.target_unchecked_into();
+        onchange={
+            let dispatch = dispatch.clone();
+            Callback::from(move |e: Event| {
+                let input: HtmlInputElement = e.target_unchecked_into();
 
-            match input.value().parse::<i8>() {
-                Ok(age) => if (MINIMUM_PLAYER_AGE..=MAXIMUM_PLAYER_AGE).contains(&age) {
-                    model.player.age = age;
-                } else {
-                    // Set default age
-                    model.player.age = MINIMUM_PLAYER_AGE;
-                    web_sys::window().unwrap().alert_with_message(format!("Cannot parse age! Only number from {} to {} is accepted", MINIMUM_PLAYER_AGE, MAXIMUM_PLAYER_AGE).as_str()).unwrap();
+                match input.value().parse::<i8>() {
+                    Ok(age) => dispatch.apply(Msg::SetPlayerAge(age)),
+                    Err(_) => dispatch.apply(Msg::Notify(Severity::Warning, Text::new("character.age_invalid").arg("min", MINIMUM_PLAYER_AGE).arg("max", MAXIMUM_PLAYER_AGE))),
                 }
-                Err(_) => web_sys::window().unwrap().alert_with_message(format!("Cannot parse age! Only number from {} to {} is accepted", MINIMUM_PLAYER_AGE, MAXIMUM_PLAYER_AGE).as_str()).unwrap(),
-            }
-        })}
+            })
+        }
         />
 
         <br/>
-        <label>{"Nationality"}</label>
+        <label>{t("character.nationality")}</label>
         <br/>
         <div class="select is-small">
-            <select oninput={dispatch.reduce_mut_callback_with(move |model, e: InputEvent|
-                model.player.nationality = Nationality::from_str(&e.target_unchecked_into::<HtmlInputElement>().value()).unwrap()
+            <select oninput={dispatch.apply_callback(move |e: InputEvent|
+                Msg::SetPlayerNationality(Nationality::from_str(&e.target_unchecked_into::<HtmlInputElement>().value()).unwrap())
             )}>
                 { NATIONALITIES
                     .iter()
                     .map(|n|
                         match n {
-                            Nationality::British => html!(<option selected={true}>{n}</option>),
-                            _otherwise => html!(<option>{n}</option>)
+                            Nationality::British => html!(<option value={n.to_string()} selected={true}>{n.name(locale())}</option>),
+                            _otherwise => html!(<option value={n.to_string()}>{n.name(locale())}</option>)
                         }
                     )
                     .collect::<Html>() }
@@ -148,45 +493,70 @@ fn show_new_character(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
         </div>
 
         <br/>
-        <label>{"Your ship name"}</label>
+        <label>{t("character.ship_name")}</label>
         <br/>
         <input class="input is-small" placeholder="Luna" required=true type="text" value={player.ship.name.to_string()}
-            onchange={dispatch.reduce_mut_callback_with(move |model, e: Event| {
+            onchange={dispatch.apply_callback(move |e: Event| {
                 let input: HtmlInputElement = e.target_unchecked_into();
 
-                model.player.ship.name = input.value();
+                Msg::SetShipName(input.value())
             })}
         />
 
         <br/>
-        <label>{"Ship Class"}</label>
+        <label>{t("character.ship_class")}</label>
         <br/>
         <div class="select is-small">
-            <select oninput={dispatch.reduce_mut_callback_with(move |model, e: InputEvent| {
+            <select oninput={dispatch.apply_callback(move |e: InputEvent| {
                 let selection = e.target_unchecked_into::<HtmlInputElement>().value();
-                let mut ship_choice = SHIPS.get(&ShipClass::from_str(&selection).unwrap()).unwrap().clone();
-                let old_ship_name = &model.player.ship.name;
-                ship_choice.name = old_ship_name.to_string();
 
-                model.player.ship = ship_choice;
+                Msg::SetShipClass(ShipClass::from_str(&selection).unwrap())
             })}>
                 {
                     SHIP_CLASSES
                     .iter()
                     .map(|n|
                         match n {
-                            ShipClass::Sloop => html!(<option selected={true}>{n}</option>),
+                            ShipClass::Sloop => html!(<option value={n.to_string()} selected={true}>{n.name(locale())}</option>),
                             // TODO: Limit to cutter, sloop and only brig when debug completed
-                            _otherwise => html!(<option>{n}</option>)
+                            _otherwise => html!(<option value={n.to_string()}>{n.name(locale())}</option>)
                         }
                     )
                     .collect::<Html>() }
             </select>
         </div>
+        <br/>
+        <label>{t("character.seed")}</label>
+        <br/>
+        <input class="input is-small" required=true type="text" value={model.seed.to_string()}
+            onchange={
+                let dispatch = dispatch.clone();
+                Callback::from(move |e: Event| {
+                    let input: HtmlInputElement = e.target_unchecked_into();
+
+                    match input.value().trim().parse::<u64>() {
+                        Ok(seed) => dispatch.apply(Msg::SetSeed(seed)),
+                        Err(_) => dispatch.apply(Msg::Notify(Severity::Warning, Text::new("character.seed_invalid"))),
+                    }
+                })
+            }
+        />
+
         <br/>
         <br/>
-        { onclick_switch_screen(dispatch, Screen::MainNavigation, "Continue") }
-        { onclick_switch_screen(dispatch, Screen::MainMenu, "Back") }
+        { onclick_styled_btn({
+            let dispatch = dispatch.clone();
+            Callback::from(move |_| {
+                dispatch.apply(Msg::SwitchScreen(Screen::MainNavigation));
+
+                let model = dispatch.get();
+                let journal = Dispatch::<Journal>::new().get();
+                Dispatch::<Saves>::new().reduce_mut(|s| {
+                    s.create(&mut LocalStorage, t_with("saves.voyage", &[("captain", &model.player.name)]), &model, &journal, now());
+                });
+            })
+        }, &t("common.continue")) }
+        { onclick_switch_screen(dispatch, Screen::MainMenu, &t("common.back")) }
         </div>
     }
 }
@@ -203,72 +573,102 @@ fn styled_progress(id: &str, label: &str, max: i32, value: i32) -> Html {
 fn battle_participant_infobox(ship: &Ship) -> Html {
     html! {
         <div class="box is-small">
-            <h4 class="title is-4">{&ship.name}</h4>
-            <p>{"Class: "} {&ship.class}</p>
-            { styled_progress("hull", "Hull", ship.hull_capacity.into(), ship.hull.into()) }
-            { styled_progress("crew", "Crew", ship.crew_capacity, ship.crew) }
-            { styled_progress("cannons", "Cannons", ship.cannons_capacity.into(), ship.cannons.into()) }
-            { styled_progress("cargos", "Total cargos", ship.cargos_capacity, ship.cargos.total_unit()) }
+            <h4 class="title is-4">{i18n::ship_name(locale(), &ship.name)}</h4>
+            <p>{t("ship.class")} {": "} {ship.class.name(locale())}</p>
+            { styled_progress("hull", &t("ship.hull"), ship.hull_capacity.into(), ship.hull.into()) }
+            { styled_progress("crew", &t("ship.crew"), ship.crew_capacity, ship.crew) }
+            { styled_progress("cannons", &t("ship.cannons"), ship.cannons_capacity.into(), ship.cannons.into()) }
+            { styled_progress("cargos", &t("ship.total_cargos"), ship.cargos_capacity, ship.cargos.total_unit()) }
         </div>
     }
 }
 
 fn show_main_navigation(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
-    let current_port = model.ports.get(&model.current_port_location).unwrap();
+    let location = model.current_port_location;
     html! {
         <div>
-            <h2 class="title is-2">{&current_port.name}</h2>
-            <h3 class="subtitle is-3">{&current_port.description}</h3>
+            <h2 class="title is-2">{location.name(locale())}</h2>
+            <h3 class="subtitle is-3">{t(&format!("port.{:?}.description", location))}</h3>
 
             <br/>
 
             <div>
-                <p>{"Date: "} {&model.date}</p>
-                <p>{"Coins: "} {&model.player.coins}</p>
-                <p>{"Food left: "} {&model.player.ship.cargos.food.unit}</p>
+                <p>{t("common.date")} {": "} {&model.date}</p>
+                <p>{t("common.coins")} {": "} {&model.player.coins}</p>
+                <p>{t("navigation.food")} {": "} {model.player.ship.cargos.unit(CargoKind::Food)}</p>
             </div>
 
+            { show_captains_log(&model) }
+
             <nav class="panel">
                 <p class="panel-tabs">
-                    <a>{ link_switch_screen(dispatch, Screen::Profile, "Profile") }</a>
-                    <a>{ link_switch_screen(dispatch, Screen::Dock, "Dock") }</a>
-                    <a>{ link_switch_screen(dispatch, Screen::Skirmish, "Skirmish") }</a>
+                    <a>{ link_switch_screen(dispatch, Screen::Profile, &t("common.profile")) }</a>
+                    <a>{ link_switch_screen(dispatch, Screen::Dock, &t("common.dock")) }</a>
+                    <a>{ link_switch_screen(dispatch, Screen::Skirmish, &t("navigation.skirmish")) }</a>
                 </p>
 
-                <a class="panel-block">
-                    { link_switch_location(dispatch, PortLocation::Barbados, "Barbados") } {" - Wealthy port"}
-                </a>
-                <a class="panel-block">
-                    { link_switch_location(dispatch, PortLocation::PortRoyal, "Port Royal") } {" - Prosperous port"}
-                </a>
-                <a class="panel-block">
-                    { link_switch_location(dispatch, PortLocation::Nassau, "Nassau") } {" - Vibrant port"}
-                </a>
+                { for PORT_LOCATIONS.iter().map(|&l| html! {
+                    <a class="panel-block">
+                        { link_switch_location(dispatch, l, &l.name(locale())) } {" - "} {t(&format!("port.{:?}.hint", l))}
+                    </a>
+                }) }
             </nav>
         </div>
     }
 }
 
 fn show_profile(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
+    let save_json = export_json(&model);
+    let save_code = export_code(&model);
+
     html! {
         <div>
             <nav class="breadcrumb has-arrow-separator" aria-label="breadcrumbs">
                 <ul>
-                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, "Navigation") }</li>
-                    <li class="is-active"><a href="#" aria-current="page">{"Profile"}</a></li>
+                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, &t("common.navigation")) }</li>
+                    <li class="is-active"><a href="#" aria-current="page">{t("common.profile")}</a></li>
                 </ul>
             </nav>
 
             <div class="box">
-                <h2>{"Profile"}</h2>
+                <h2>{t("common.profile")}</h2>
                 <hr/>
 
                 <ul>
-                    <li>{"Name: "} {&model.player.name}</li>
-                    <li>{"Age: "} {&model.player.age}</li>
-                    <li>{"Nationality: "} {&model.player.nationality}</li>
+                    <li>{t("character.name")} {": "} {&model.player.name}</li>
+                    <li>{t("character.age")} {": "} {&model.player.age}</li>
+                    <li>{t("character.nationality")} {": "} {model.player.nationality.name(locale())}</li>
+                    <li>{t("character.seed")} {": "} {&model.seed}</li>
                 </ul>
             </div>
+
+            <div class="box">
+                <h2>{t("profile.journal")}</h2>
+                <hr/>
+
+                { onclick_styled_btn(Callback::from(|_| {
+                    let journal = Dispatch::<Journal>::new().get();
+                    download_file("lunar-journal.json", journal.to_json().unwrap().as_str());
+                }), &t("profile.export_journal")) }
+                { onclick_styled_btn(dispatch.apply_callback(move |_| {
+                    let report = match replay(&Dispatch::<Journal>::new().get()) {
+                        Ok(replayed) if replayed == *model => (Severity::Success, Text::new("profile.journal_matches")),
+                        Ok(_) => (Severity::Warning, Text::new("profile.journal_differs")),
+                        Err(divergence) => (Severity::Danger, divergence.text()),
+                    };
+                    Msg::Notify(report.0, report.1)
+                }), &t("profile.verify_journal")) }
+            </div>
+
+            <div class="box">
+                <h2>{t("profile.export")}</h2>
+                <hr/>
+
+                { onclick_styled_btn(Callback::from(move |_| download_file("lunar-save.json", save_json.as_str())), &t("profile.download_save")) }
+                <br/>
+                <label>{t("common.save_code")}</label>
+                <textarea class="textarea is-small" readonly=true value={save_code} />
+            </div>
         </div>
     }
 }
@@ -278,34 +678,115 @@ fn show_dock_tavern_hire_crew(model: Rc<Model>, dispatch: &Dispatch<Model>) -> H
         <div>
             <nav class="breadcrumb has-arrow-separator" aria-label="breadcrumbs">
                 <ul>
-                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, "Navigation") }</li>
-                    <li>{ link_switch_screen(dispatch, Screen::Dock, "Dock") }</li>
-                    <li>{ link_switch_screen(dispatch, Screen::DockTavern, "Tavern") }</li>
-                    <li class="is-active"><a href="#" aria-current="page">{"Hire Crew"}</a></li>
+                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, &t("common.navigation")) }</li>
+                    <li>{ link_switch_screen(dispatch, Screen::Dock, &t("common.dock")) }</li>
+                    <li>{ link_switch_screen(dispatch, Screen::DockTavern, &t("common.tavern")) }</li>
+                    <li class="is-active"><a href="#" aria-current="page">{t("tavern.hire_crew")}</a></li>
                 </ul>
             </nav>
             { battle_participant_infobox(&model.player.ship) }
 
-            <p>{"Cost to hire all: "} {&model.player.ship.cost_to_hire() }</p>
-            { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::HireCrew(model.player.coins)), "Hire until full") }
-            { onclick_switch_screen(dispatch, Screen::DockTavern, "Back") }
+            <p>{t("tavern.cost_to_hire")} {": "} {&model.player.ship.cost_to_hire() }</p>
+            { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::HireCrew(model.player.coins)), &t("tavern.hire_until_full")) }
+            { onclick_switch_screen(dispatch, Screen::DockTavern, &t("common.back")) }
         </div>
     }
 }
 
-fn show_dock_tavern(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
+// Borrows or repays the amount typed in, cut down to what is owed when
+// repaying.
+#[function_component]
+fn Moneylender() -> Html {
+    let (model, dispatch) = use_store::<Model>();
+    let amount = use_state(|| 100);
+    let oninput = {
+        let amount = amount.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            amount.set(input.value().trim().parse::<i32>().unwrap_or(1).max(1));
+        })
+    };
+    let button = |label: &str, coins: i32, msg: fn(i32) -> Msg| {
+        onclick_styled_btn(
+            dispatch.apply_callback(move |_| msg(coins)),
+            &t_with(label, &[("coins", &coins)]),
+        )
+    };
+
+    let terms = match &model.player.loan {
+        Some(loan) => html! {
+            <>
+                <p>{t_with("moneylender.owed", &[
+                    ("owed", &loan.owed),
+                    ("lender", &loan.lender.name(locale())),
+                    ("due", &loan.due),
+                ])}</p>
+                { ternary!(loan.missed > 0, html! {
+                    <p class="has-text-danger">{t_with("moneylender.missed", &[("missed", &loan.missed)])}</p>
+                }, html!()) }
+                { button("moneylender.repay", (*amount).min(loan.owed), Msg::RepayLoan) }
+                { button("moneylender.repay_all", loan.owed, Msg::RepayLoan) }
+            </>
+        },
+        None => {
+            let limit = lending::credit_limit(&model);
+            html! {
+                <>
+                    <p>{t_with("moneylender.terms", &[
+                        ("limit", &limit),
+                        ("interest", &lending::DAILY_INTEREST_PERMILLE),
+                        ("days", &lending::LOAN_DAYS),
+                    ])}</p>
+                    <p class="help">{t("moneylender.warning")}</p>
+                    { button("moneylender.borrow", *amount, Msg::TakeLoan) }
+                    { button("moneylender.borrow_most", limit, Msg::TakeLoan) }
+                </>
+            }
+        }
+    };
+
+    html! {
+        <div class="box">
+            <p>{t("common.coins")} {": "} {model.player.coins}</p>
+            <p>{t("moneylender.net_worth")} {": "} {lending::net_worth(&model)}</p>
+            { terms }
+            <label>{t("moneylender.amount")}</label>
+            <input class="input is-small" type="number" min="1" value={amount.to_string()} {oninput} />
+        </div>
+    }
+}
+
+fn show_dock_tavern_moneylender(dispatch: &Dispatch<Model>) -> Html {
     html! {
         <div>
             <nav class="breadcrumb has-arrow-separator" aria-label="breadcrumbs">
                 <ul>
-                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, "Navigation") }</li>
-                    <li>{ link_switch_screen(dispatch, Screen::Dock, "Dock") }</li>
-                    <li class="is-active"><a href="#" aria-current="page">{"Tavern"}</a></li>
+                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, &t("common.navigation")) }</li>
+                    <li>{ link_switch_screen(dispatch, Screen::Dock, &t("common.dock")) }</li>
+                    <li>{ link_switch_screen(dispatch, Screen::DockTavern, &t("common.tavern")) }</li>
+                    <li class="is-active"><a href="#" aria-current="page">{t("tavern.moneylender")}</a></li>
                 </ul>
             </nav>
-            <h2>{"Tavern screen"}</h2>
-            { onclick_switch_screen(dispatch, Screen::TavernHireCrew, "Hire crew") }
-            { onclick_switch_screen(dispatch, Screen::Dock, "Back") }
+            <Moneylender />
+            { onclick_switch_screen(dispatch, Screen::DockTavern, &t("common.back")) }
+        </div>
+    }
+}
+
+fn show_dock_tavern(_model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
+    html! {
+        <div>
+            <nav class="breadcrumb has-arrow-separator" aria-label="breadcrumbs">
+                <ul>
+                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, &t("common.navigation")) }</li>
+                    <li>{ link_switch_screen(dispatch, Screen::Dock, &t("common.dock")) }</li>
+                    <li class="is-active"><a href="#" aria-current="page">{t("common.tavern")}</a></li>
+                </ul>
+            </nav>
+            <h2>{t("tavern.title")}</h2>
+            { onclick_switch_screen(dispatch, Screen::TavernHireCrew, &t("tavern.hire_crew")) }
+            { onclick_switch_screen(dispatch, Screen::TavernMoneylender, &t("tavern.moneylender")) }
+            { onclick_switch_screen(dispatch, Screen::Dock, &t("common.back")) }
         </div>
     }
 }
@@ -315,76 +796,224 @@ fn show_dock(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
         <div>
             <nav class="breadcrumb has-arrow-separator" aria-label="breadcrumbs">
                 <ul>
-                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, "Navigation") }</li>
-                    <li class="is-active"><a href="#" aria-current="page">{"Dock"}</a></li>
+                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, &t("common.navigation")) }</li>
+                    <li class="is-active"><a href="#" aria-current="page">{t("common.dock")}</a></li>
                 </ul>
             </nav>
 
             <nav class="panel">
                 <p class="panel-tabs">
-                    <a>{ link_switch_screen(dispatch, Screen::DockTavern, "Tavern") }</a>
-                    <a>{ link_switch_screen(dispatch, Screen::DockMarket, "Market") }</a>
-                    <a>{ link_switch_screen(dispatch, Screen::DockShipyard, "Shipyard") }</a>
+                    <a>{ link_switch_screen(dispatch, Screen::DockTavern, &t("common.tavern")) }</a>
+                    <a>{ link_switch_screen(dispatch, Screen::DockMarket, &t("common.market")) }</a>
+                    { ternary!(model.ports[&model.current_port_location].black_market, html! {
+                        <a>{ link_switch_screen(dispatch, Screen::DockBlackMarket, &t("common.black_market")) }</a>
+                    }, html!()) }
+                    <a>{ link_switch_screen(dispatch, Screen::DockShipyard, &t("common.shipyard")) }</a>
                 </p>
             </nav>
         </div>
     }
 }
 
-fn cargo_item(
-    buy_callback: Callback<MouseEvent>,
-    sell_callback: Callback<MouseEvent>,
-    player_cargo: &Cargo,
-    port_cargo: &Cargo,
-    name: &str,
-) -> Html {
+const CHART_WIDTH: i32 = 200;
+const CHART_HEIGHT: i32 = 40;
+
+// Sparkline of a good's recent prices, oldest on the left, with the lowest and
+// highest price marked and the average as a dashed line.
+fn price_chart(points: &VecDeque<PricePoint>) -> Html {
+    if points.len() < 2 {
+        return html! { <p class="help">{t("market.history_empty")}</p> };
+    }
+
+    let prices = points.iter().map(|p| p.price);
+    let (min, max) = (prices.clone().min().unwrap(), prices.clone().max().unwrap());
+    let average = prices.sum::<i32>() / points.len() as i32;
+    let x = |i: usize| i as i32 * CHART_WIDTH / (points.len() as i32 - 1);
+    let y = |price: i32| CHART_HEIGHT - (price - min) * CHART_HEIGHT / (max - min).max(1);
+
+    let line = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| format!("{},{}", x(i), y(p.price)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let marker = |price: i32, color: &str| {
+        let i = points.iter().position(|p| p.price == price).unwrap();
+        let point = &points[i];
+        html! {
+            <circle cx={x(i).to_string()} cy={y(price).to_string()} r="2" fill={color.to_string()}>
+                <title>{format!("{}: {} ({})", point.date, point.price, point.unit)}</title>
+            </circle>
+        }
+    };
+
+    html! {
+        <div>
+            <svg viewBox={format!("-3 -3 {} {}", CHART_WIDTH + 6, CHART_HEIGHT + 6)} width="100%" height="60">
+                <line x1="0" x2={CHART_WIDTH.to_string()} y1={y(average).to_string()} y2={y(average).to_string()}
+                    stroke="grey" stroke-width="0.5" stroke-dasharray="3 2" />
+                <polyline points={line} fill="none" stroke="currentColor" stroke-width="1" />
+                { marker(min, "green") }
+                { marker(max, "red") }
+            </svg>
+            <p class="help">{t_with("market.history_summary", &[("min", &min), ("max", &max), ("average", &average)])}</p>
+        </div>
+    }
+}
+
+type TradeMsg = fn(PortLocation, CargoKind, i32) -> Msg;
+
+#[derive(Properties, PartialEq)]
+struct CargoItemProps {
+    kind: CargoKind,
+    venue: Venue,
+}
+
+// One good of the market, traded by the quantity typed in. The quantity is
+// cut down to what the player can actually buy or sell, and the buttons show
+// the total before anything changes hands.
+#[function_component]
+fn CargoItem(props: &CargoItemProps) -> Html {
+    let (model, dispatch) = use_store::<Model>();
+    let quantity = use_state(|| 1);
+    let (kind, venue) = (props.kind, props.venue);
+    let l = model.current_port_location;
+
+    let quote = quote_at(&model, l, kind, venue);
+    let in_port = model.ports[&l].cargos.unit(kind);
+    let held = model.player.ship.cargos.unit(kind);
+    let most = max_buy(&model, l, kind, venue);
+    // Past the cap, the engine explains why nothing can be traded.
+    let capped = |units: i32, cap: i32| ternary!(cap > 0, units.min(cap), units);
+    let to_buy = capped(*quantity, most);
+    let to_sell = capped(*quantity, held);
+
+    let oninput = {
+        let quantity = quantity.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            quantity.set(input.value().trim().parse::<i32>().unwrap_or(1).max(1));
+        })
+    };
+    let (buy, sell): (TradeMsg, TradeMsg) = match venue {
+        Venue::Market => (Msg::BuyCargo, Msg::SellCargo),
+        Venue::BlackMarket => (Msg::BuyContraband, Msg::SellContraband),
+    };
+    // Totals with tariffs, and the tariffs themselves.
+    let buying = |units: i32| {
+        let cost = buy_cost(&model, l, kind, venue, units);
+        let duty = export_duty(&model, l, venue, cost);
+        (cost + duty, duty)
+    };
+    let selling = |units: i32| {
+        let proceeds = sell_proceeds(&model, l, kind, venue, units);
+        let duty = import_duty(&model, l, venue, proceeds);
+        (proceeds - duty, duty)
+    };
+    let trade = |units: i32, label: &str, (coins, duty): (i32, i32), msg: TradeMsg| {
+        let mut text = t_with(label, &[("units", &units), ("coins", &coins)]);
+        if duty > 0 {
+            text = format!("{} {}", text, t_with("market.tariff", &[("tariff", &duty)]));
+        }
+        // Only the shortcuts can come to nothing, and then there is nothing to send.
+        html! {
+            <button class="button" disabled={units < 1} onclick={dispatch.apply_callback(move |_| msg(l, kind, units))}>{text}</button>
+        }
+    };
+
     html! {
         <li>
-            <p>{name}</p>
-            <p>{"Price: "} {port_cargo.price}</p>
-            <p>{"Available unit: "} {port_cargo.unit}</p>
-            <p>{"In your cargo space: "} {player_cargo.unit}</p>
-            { onclick_styled_btn(buy_callback, "Buy 1") }
-            { onclick_styled_btn(sell_callback, "Sell 1")}
+            <p>{kind.name(locale())}</p>
+            <p>{t("market.buy_price")} {": "} {quote.buy}</p>
+            <p>{t("market.sell_price")} {": "} {quote.sell}</p>
+            <p>{t("market.available")} {": "} {in_port}</p>
+            <p>{t("market.in_cargo")} {": "} {held}</p>
+            <label>{t("market.history")}</label>
+            { price_chart(model.ports[&l].history.get(&kind).unwrap_or(&VecDeque::new())) }
+            <label>{t("market.quantity")}</label>
+            <input class="input is-small" type="number" min="1" value={quantity.to_string()} {oninput} />
+            { trade(to_buy, "market.buy", buying(to_buy), buy) }
+            { trade(to_sell, "market.sell", selling(to_sell), sell) }
+            { trade(most, "market.buy_max", buying(most), buy) }
+            { trade(held, "market.sell_all", selling(held), sell) }
         </li>
     }
 }
 
-fn cargo_market(model: &Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
-    let current_location = model.current_port_location;
-    let player_cargos = &model.player.ship.cargos;
-    let ports = &model.ports.clone();
-    let port_cargos = ports.get(&current_location).unwrap().cargos.clone();
+// What the port charges the player, itemized.
+fn show_levies(port: &Port, player: &Player) -> Html {
+    let levies = tariffs::levies(port, player);
+    let nation = port.nationality.name(locale());
+    let relation = tariffs::relation(&player.nationality, &port.nationality).name(locale());
+
+    html! {
+        <div class="box">
+            <p>{t_with("market.relation", &[("nation", &nation), ("relation", &relation)])}</p>
+            <p>{t("market.docking_fee")} {": "} {levies.docking_fee}</p>
+            <p>{t("market.import_tariff")} {": "} {levies.import_percent} {"%"}</p>
+            <p>{t("market.export_tariff")} {": "} {levies.export_percent} {"%"}</p>
+        </div>
+    }
+}
+
+// The regular market deals in every good that isn't contraband at the port,
+// and the black market in the rest.
+fn cargo_market(model: &Model, venue: Venue) -> Html {
+    let port = &model.ports[&model.current_port_location];
+    let (traded, contraband): (Vec<CargoKind>, Vec<CargoKind>) = CARGO_KINDS
+        .iter()
+        .partition(|&&kind| is_contraband(&CONTENT, port, kind) == (venue == Venue::BlackMarket));
+    let note = match venue {
+        Venue::Market if !contraband.is_empty() => {
+            let goods = contraband
+                .iter()
+                .map(|kind| kind.name(locale()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            html! { <p class="help">{t_with("market.contraband", &[("goods", &goods)])}</p> }
+        }
+        Venue::Market => html!(),
+        Venue::BlackMarket => html! { <p class="help">{t("black_market.note")}</p> },
+    };
+    let levies = match venue {
+        Venue::Market => show_levies(port, &model.player),
+        Venue::BlackMarket => html!(),
+    };
 
     html! {
         <div>
+            { levies }
+            { note }
             <ul>
-                { cargo_item(dispatch.apply_callback(move |_| Msg::BuyCargo(current_location, port_cargos.food)), dispatch.apply_callback(move |_| Msg::SellCargo(current_location, port_cargos.food)), &player_cargos.food, &port_cargos.food, "Food") }
-                { cargo_item(dispatch.apply_callback(move |_| Msg::BuyCargo(current_location, port_cargos.wood)), dispatch.apply_callback(move |_| Msg::SellCargo(current_location, port_cargos.wood)), &player_cargos.wood, &port_cargos.wood, "Wood") }
-                { cargo_item(dispatch.apply_callback(move |_| Msg::BuyCargo(current_location, port_cargos.sugar)), dispatch.apply_callback(move |_| Msg::SellCargo(current_location, port_cargos.sugar)), &player_cargos.sugar, &port_cargos.sugar, "Sugar") }
+                { for traded.into_iter().map(|kind| html! { <CargoItem {kind} {venue} /> }) }
             </ul>
         </div>
     }
 }
 
-fn show_dock_market(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
+fn show_dock_market(model: Rc<Model>, dispatch: &Dispatch<Model>, venue: Venue) -> Html {
+    let title = match venue {
+        Venue::Market => t("common.market"),
+        Venue::BlackMarket => t("common.black_market"),
+    };
+
     html! {
         <div>
             <nav class="breadcrumb has-arrow-separator" aria-label="breadcrumbs">
                 <ul>
-                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, "Navigation") }</li>
-                    <li>{ link_switch_screen(dispatch, Screen::Dock, "Dock") }</li>
-                    <li class="is-active"><a href="#" aria-current="page">{"Market"}</a></li>
+                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, &t("common.navigation")) }</li>
+                    <li>{ link_switch_screen(dispatch, Screen::Dock, &t("common.dock")) }</li>
+                    <li class="is-active"><a href="#" aria-current="page">{&title}</a></li>
                 </ul>
             </nav>
 
-            <h2>{"Market"}</h2>
+            <h2>{&title}</h2>
             <hr/>
             <div class="box">
-                <p>{"Coins: "} {&model.player.coins}</p>
-                <p>{ styled_progress("cargos", "Player cargos", model.player.ship.cargos_capacity.into(), model.player.ship.cargos.total_unit()) }</p>
+                <p>{t("common.coins")} {": "} {&model.player.coins}</p>
+                <p>{ styled_progress("cargos", &t("market.player_cargos"), model.player.ship.cargos_capacity.into(), model.player.ship.cargos.total_unit()) }</p>
                 <br/>
-                { cargo_market(&model, dispatch) }
+                { cargo_market(&model, venue) }
             </div>
         </div>
     }
@@ -395,31 +1024,33 @@ fn show_dock_shipyard(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
         <div>
             <nav class="breadcrumb has-arrow-separator" aria-label="breadcrumbs">
                 <ul>
-                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, "Navigation") }</li>
-                    <li>{ link_switch_screen(dispatch, Screen::Dock, "Dock") }</li>
-                    <li class="is-active"><a href="#" aria-current="page">{"Shipyard"}</a></li>
+                    <li>{ link_switch_screen(dispatch, Screen::MainNavigation, &t("common.navigation")) }</li>
+                    <li>{ link_switch_screen(dispatch, Screen::Dock, &t("common.dock")) }</li>
+                    <li class="is-active"><a href="#" aria-current="page">{t("common.shipyard")}</a></li>
                 </ul>
             </nav>
 
             { battle_participant_infobox(&model.player.ship) }
 
-            <p>{"Cost to repair: "} { &model.player.ship.cost_to_repair() }</p>
-            {SHIP_CLASSES.iter().map(|x| onclick_styled_btn(dispatch.apply_callback(move |_| Msg::BuyAndReplaceShip(*x)), "")).collect::<Html>() }
+            <p>{t("shipyard.cost_to_repair")} {": "} { &model.player.ship.cost_to_repair() }</p>
+            {SHIP_CLASSES.iter().map(|x| {
+                let question = Text::new("shipyard.buy_question").text("class", x.text());
+                onclick_confirmed_btn(dispatch, Msg::BuyAndReplaceShip(*x), question, &t_with("shipyard.buy", &[("class", &x.name(locale()))]))
+            }).collect::<Html>() }
 
-            { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::RepairShip(model.player.coins)), "Repair all") }
-            { onclick_switch_screen(dispatch, Screen::Dock, "Back") }
+            { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::RepairShip(model.player.coins)), &t("shipyard.repair_all")) }
+            { onclick_switch_screen(dispatch, Screen::Dock, &t("common.back")) }
         </div>
     }
 }
 
-fn show_skirmish(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
+fn show_skirmish(_model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
     html! {
         <div>
-            { debug_header(dispatch) }
-            <h2>{"Skirmish"}</h2>
+                <h2>{t("skirmish.title")}</h2>
 
-            { onclick_switch_screen(dispatch, Screen::SkirmishChase, "Chase") }
-            { onclick_switch_screen(dispatch, Screen::MainNavigation, "Abort") }
+            { onclick_switch_screen(dispatch, Screen::SkirmishChase, &t("skirmish.chase")) }
+            { onclick_switch_screen(dispatch, Screen::MainNavigation, &t("skirmish.abort")) }
         </div>
     }
 }
@@ -439,9 +1070,9 @@ fn show_skirmish_chase(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
                 </div>
                 <hr/>
 
-                { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishChaseClose), "Close") }
-                { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishChaseDistant), "Distant") }
-                { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishChaseBroadside), "Broadside") }
+                { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishChaseClose), &t("skirmish.close")) }
+                { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishChaseDistant), &t("skirmish.distant")) }
+                { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishChaseBroadside), &t("skirmish.broadside")) }
             </div>
         </div>
     }
@@ -453,7 +1084,7 @@ fn show_skirmish_loot(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
 
     html! {
         <div>
-            <h2>{"Loot"}</h2>
+            <h2>{t("skirmish.loot")}</h2>
 
             { battle_participant_infobox(&model.player.ship) }
             { battle_participant_infobox(&model.enemy.as_ref().unwrap().ship) }
@@ -462,19 +1093,17 @@ fn show_skirmish_loot(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
                 { if enemy_cargos.total_unit() > 0 && player_ship.cargos.total_unit() < player_ship.cargos_capacity {
                 html! {
                     <>
-                    { ternary!(enemy_cargos.food.unit > 0, onclick_styled_btn(dispatch.apply_callback(move |_| Msg::TakeEnemyCargo(CargoKind::Food)), "Take 1"), html!()) }
-                    { ternary!(enemy_cargos.wood.unit > 0, onclick_styled_btn(dispatch.apply_callback(move |_| Msg::TakeEnemyCargo(CargoKind::Wood)), "Take 1"), html!()) }
-                    { ternary!(enemy_cargos.sugar.unit > 0, onclick_styled_btn(dispatch.apply_callback(move |_| Msg::TakeEnemyCargo(CargoKind::Sugar)), "Take 1"), html!()) }
+                    { for CARGO_KINDS.iter().filter(|&&kind| enemy_cargos.unit(kind) > 0).map(|&kind| onclick_styled_btn(dispatch.apply_callback(move |_| Msg::TakeEnemyCargo(kind)), &t_with("skirmish.take_one", &[("cargo", &kind.name(locale()))]))) }
                     </>
                 }
                 }
                 else {
-                    html!{ "Enemy have no loot." }
+                    html!{ t("skirmish.no_loot") }
                 }
             }
             </p>
 
-            { onclick_switch_screen(dispatch, Screen::MainNavigation, "Continue") }
+            { onclick_switch_screen(dispatch, Screen::MainNavigation, &t("common.continue")) }
         </div>
     }
 }
@@ -482,8 +1111,7 @@ fn show_skirmish_loot(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
 fn show_skirmish_battle(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
     html! {
         <div>
-            { debug_header(dispatch) }
-            <h2>{"Battle!"}</h2>
+                <h2>{t("skirmish.battle")}</h2>
             { battle_participant_infobox(&model.player.ship) }
             { battle_participant_infobox(&model.enemy.as_ref().unwrap().ship) }
             <hr/>
@@ -493,41 +1121,33 @@ fn show_skirmish_battle(model: Rc<Model>, dispatch: &Dispatch<Model>) -> Html {
                     if enemy.ship.crew < MINIMUM_SHIP_CREW.into() {
                         html! {
                             <>
-                            { onclick_switch_screen(dispatch, Screen::SkirmishLoot, "Loot enemy") }
-                            { onclick_switch_screen(dispatch, Screen::MainNavigation, "Continue") }
+                            { onclick_switch_screen(dispatch, Screen::SkirmishLoot, &t("skirmish.loot_enemy")) }
+                            { onclick_switch_screen(dispatch, Screen::MainNavigation, &t("common.continue")) }
                             </>
                         }
                     }
                     else {
                         html! {
                             <>
-                            { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishBattleSwingSword), "Sword Attack") }
-                            { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishBattleShootFalconet), "Shoot Falconet volleys") }
+                            { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishBattleSwingSword), &t("skirmish.sword")) }
+                            { onclick_styled_btn(dispatch.apply_callback(move |_| Msg::SkirmishBattleShootFalconet), &t("skirmish.falconet")) }
                             </>
                         }
                     }
                 },
-                None => { onclick_switch_screen(dispatch, Screen::MainNavigation, "Back") },
+                None => { onclick_switch_screen(dispatch, Screen::MainNavigation, &t("common.back")) },
             }}
         </div>
     }
 }
 
-fn show_game_lost(model: Rc<Model>, dispatch: &Dispatch<Model>, reason: &GameLostReason) -> Html {
+fn show_game_lost(_model: Rc<Model>, dispatch: &Dispatch<Model>, reason: &GameLostReason) -> Html {
     html! {
         <div>
-            <p>
-            {
-                match reason {
-                    GameLostReason::ShipSunk => html!("Your ship and your crew sunk into the deep abyss of the ocean. RIP"),
-                    GameLostReason::AllCrewDied => html!("All your crew died and your fate is left to your enemy. RIP"),
-                    GameLostReason::FoodMutiny => html!("You ran out of food to feed your crew! One of your crew members took the matter into their own hand and managed to cause a mutiny! Where do you wanna go now? RIP "),
-                }
-            }
-            </p>
+            <p>{t(&format!("game_lost.{:?}", reason))}</p>
 
             <br/>
-            { onclick_switch_screen(dispatch, Screen::MainMenu, "Back to main menu") }
+            { onclick_switch_screen(dispatch, Screen::MainMenu, &t("game_lost.back")) }
         </div>
     }
 }
@@ -535,22 +1155,61 @@ fn show_game_lost(model: Rc<Model>, dispatch: &Dispatch<Model>, reason: &GameLos
 #[function_component]
 pub fn View() -> Html {
     let (model, dispatch) = use_store::<Model>();
+    let saves = use_store_value::<Saves>();
+    let recovery = use_store_value::<Recovery>();
+    // Only to render again when the language changes, see `locale`.
+    use_store_value::<Settings>();
 
-    root_container(match model.current_screen {
+    let screen = model.current_screen.clone();
+    use_effect(move || {
+        routes::sync_url(&screen);
+        || ()
+    });
+
+    if !recovery.broken.is_empty() {
+        return root_container(show_recovery(&recovery));
+    }
+
+    let overlays = html! {
+        <>
+            { show_toasts(&model, &dispatch) }
+            { show_confirmation_modal(&model, &dispatch) }
+            { show_deletion_modal(&saves) }
+        </>
+    };
+
+    let screen = match model.current_screen {
         Screen::About => show_about(model, &dispatch),
-        Screen::MainMenu => show_main_menu(model, &dispatch),
+        Screen::MainMenu => show_main_menu(model, &dispatch, &saves),
+        Screen::SaveSlots => show_save_slots(model, &dispatch, &saves),
+        Screen::ImportSave => html! { <ImportSave /> },
         Screen::NewCharacter => show_new_character(model, &dispatch),
         Screen::MainNavigation => show_main_navigation(model, &dispatch),
         Screen::Profile => show_profile(model, &dispatch),
         Screen::Dock => show_dock(model, &dispatch),
         Screen::DockTavern => show_dock_tavern(model, &dispatch),
         Screen::TavernHireCrew => show_dock_tavern_hire_crew(model, &dispatch),
-        Screen::DockMarket => show_dock_market(model, &dispatch),
+        Screen::TavernMoneylender => show_dock_tavern_moneylender(&dispatch),
+        Screen::DockMarket => show_dock_market(model, &dispatch, Venue::Market),
+        Screen::DockBlackMarket => show_dock_market(model, &dispatch, Venue::BlackMarket),
         Screen::DockShipyard => show_dock_shipyard(model, &dispatch),
         Screen::Skirmish => show_skirmish(model, &dispatch),
         Screen::SkirmishChase => show_skirmish_chase(model, &dispatch),
         Screen::SkirmishBattle => show_skirmish_battle(model, &dispatch),
         Screen::SkirmishLoot => show_skirmish_loot(model, &dispatch),
         Screen::GameLost(reason) => show_game_lost(model, &dispatch, &reason),
+    };
+
+    #[cfg(feature = "dev-tools")]
+    let console = html! { <crate::dev_console::DevConsole /> };
+    #[cfg(not(feature = "dev-tools"))]
+    let console = html!();
+
+    root_container(html! {
+        <>
+            { overlays }
+            { screen }
+            { console }
+        </>
     })
 }
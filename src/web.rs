@@ -0,0 +1,99 @@
+//! Glue between the engine and the browser stores, only built with the `web`
+//! feature. Every message goes through the engine, and is then recorded in
+//! the journal and saved into the active slot.
+
+use std::rc::Rc;
+
+use web_sys::window;
+use yewdux::prelude::*;
+
+use crate::engine;
+use crate::invariants::{self, Violation};
+use crate::journal::Journal;
+use crate::model::*;
+use crate::saves::{self, Saves, Storage};
+
+/// The browser's local storage. Like yewdux, writes that fail, e.g. over the
+/// quota, are dropped.
+pub struct LocalStorage;
+
+impl LocalStorage {
+    fn storage() -> Option<web_sys::Storage> {
+        window()?.local_storage().ok().flatten()
+    }
+}
+
+impl Storage for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        Self::storage()?.get_item(key).ok().flatten()
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        if let Some(storage) = Self::storage() {
+            let _ = storage.set_item(key, value);
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(storage) = Self::storage() {
+            let _ = storage.remove_item(key);
+        }
+    }
+}
+
+/// Plays `model` in place of the current game, once it passes the same checks
+/// as an imported save. The journal starts over from it.
+pub fn load(model: Model) -> Result<(), Violation> {
+    invariants::check(&model)?;
+    Dispatch::<Journal>::new().set(Journal::starting_from(&model));
+    Dispatch::<Model>::new().set(model);
+    Ok(())
+}
+
+// A confirmed reset starts over just like a direct one.
+fn is_reset(msg: &Msg, model: &Model) -> bool {
+    match msg {
+        Msg::ResetModel => true,
+        Msg::Confirm => matches!(
+            &model.pending_confirmation,
+            Some(c) if *c.msg == Msg::ResetModel
+        ),
+        _ => false,
+    }
+}
+
+impl Reducer<Model> for Msg {
+    fn apply(&self, mut model: Rc<Model>) -> Rc<Model> {
+        let state = Rc::make_mut(&mut model);
+        let seed_before = state.seed;
+        let reset = is_reset(self, state);
+
+        // Refusals are written to the captain's log, shown by the view.
+        let result = engine::apply(state, self.clone());
+
+        Dispatch::<Journal>::new().reduce_mut(|j| {
+            // A reset starts a brand new game, and so a brand new journal.
+            if reset {
+                *j = Journal::new(state.seed);
+            } else {
+                if j.entries.is_empty() {
+                    j.seed = seed_before;
+                }
+                j.record(self.clone(), state);
+            }
+        });
+
+        let journal = Dispatch::<Journal>::new().get();
+        Dispatch::<Saves>::new().reduce_mut(|s| {
+            // Resetting only detaches the game from its slot, the save is kept.
+            // Refused and UI-only messages are saved along with the next action.
+            if reset {
+                s.activate(&mut LocalStorage, None);
+            } else if result.is_ok() && !self.is_ui_only() {
+                s.save_active(&mut LocalStorage, state, &journal, saves::now());
+            }
+        });
+
+        model
+    }
+}
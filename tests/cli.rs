@@ -0,0 +1,39 @@
+//! Scripted games through the terminal frontend print the same as their
+//! golden file, and typos are reported instead of ending the game.
+//!
+//! `tests/fixtures/cli/<name>.in` is fed to `lunar-cli --seed 7`, and its
+//! output has to match `<name>.out`. Run with `UPDATE_GOLDEN=1` to write the
+//! output files again after a deliberate change.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn play(name: &str) {
+    let dir = format!("{}/tests/fixtures/cli", env!("CARGO_MANIFEST_DIR"));
+    let input = fs::read(format!("{}/{}.in", dir, name)).unwrap();
+
+    let mut cli = Command::new(env!("CARGO_BIN_EXE_lunar-cli"))
+        .args(["--seed", "7"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    cli.stdin.take().unwrap().write_all(&input).unwrap();
+    let output = cli.wait_with_output().unwrap();
+    assert!(output.status.success(), "{} did not finish", name);
+    let output = String::from_utf8(output.stdout).unwrap();
+
+    let golden = format!("{}/{}.out", dir, name);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(golden, output).unwrap();
+    } else {
+        assert_eq!(output, fs::read_to_string(golden).unwrap());
+    }
+}
+
+#[test]
+fn unknown_nationalities_and_ship_classes_are_reported() {
+    play("bad_character");
+}
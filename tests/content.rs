@@ -0,0 +1,146 @@
+//! Content sets other than the embedded one load and start a playable game,
+//! and data the rules cannot play with is refused with the file to blame.
+
+use lunar::content::{Content, ContentError};
+use lunar::invariants;
+use lunar::market;
+use lunar::model::*;
+use serde_json::Value;
+
+const CARGOS_JSON: &str = include_str!("../assets/data/cargos.json");
+const SHIPS_JSON: &str = include_str!("../assets/data/ships.json");
+const PORTS_JSON: &str = include_str!("../assets/data/ports.json");
+
+// Loads the embedded content after `edit` changed one of its files.
+fn edited(file: &str, edit: impl FnOnce(&mut Vec<Value>)) -> Result<Content, ContentError> {
+    let mut files = [CARGOS_JSON, SHIPS_JSON, PORTS_JSON].map(String::from);
+    let i = ["cargos.json", "ships.json", "ports.json"]
+        .iter()
+        .position(|f| *f == file)
+        .unwrap();
+    let mut entries = serde_json::from_str::<Vec<Value>>(&files[i]).unwrap();
+    edit(&mut entries);
+    files[i] = serde_json::to_string(&entries).unwrap();
+
+    Content::from_json(&files[0], &files[1], &files[2])
+}
+
+fn refused(file: &str, edit: impl FnOnce(&mut Vec<Value>)) -> ContentError {
+    match edited(file, edit) {
+        Ok(_) => panic!("broken {} was accepted", file),
+        Err(e) => e,
+    }
+}
+
+fn ship<'a>(entries: &'a mut [Value], class: &str) -> &'a mut Value {
+    entries.iter_mut().find(|e| e["class"] == class).unwrap()
+}
+
+#[test]
+fn the_embedded_content_is_valid() {
+    assert!(Content::from_json(CARGOS_JSON, SHIPS_JSON, PORTS_JSON).unwrap() == *CONTENT);
+}
+
+#[test]
+fn other_content_starts_a_playable_game() {
+    let content = edited("ships.json", |ships| {
+        let sloop = ship(ships, "Sloop");
+        sloop["price"] = 9_999.into();
+        sloop["cannons"] = MINIMUM_SHIP_CANNONS.into();
+    })
+    .unwrap();
+    let content = Content {
+        ports: edited("ports.json", |ports| {
+            // A port that lists no goods stocks all of them at their target.
+            ports[0]["cargos"] = serde_json::json!({});
+        })
+        .unwrap()
+        .ports,
+        ..content
+    };
+
+    let model = Model::with_content(&content);
+    assert_eq!(model.player.ship.price, 9_999);
+    assert_eq!(model.player.ship.cannons, i16::from(MINIMUM_SHIP_CANNONS));
+    assert!(invariants::check(&model).is_ok());
+    for port in model.ports.values() {
+        assert_eq!(port.cargos.iter().count(), CARGO_KINDS.len());
+    }
+}
+
+#[test]
+fn prices_come_from_the_content_the_game_was_built_with() {
+    let mut cargos = serde_json::from_str::<Vec<Value>>(CARGOS_JSON).unwrap();
+    for cargo in cargos.iter_mut() {
+        cargo["base_price"] = (cargo["base_price"].as_i64().unwrap() * 10).into();
+    }
+    let mut ports = serde_json::from_str::<Vec<Value>>(PORTS_JSON).unwrap();
+    ports[0]["cargos"] = serde_json::json!({});
+    let content = Content::from_json(
+        &serde_json::to_string(&cargos).unwrap(),
+        SHIPS_JSON,
+        &serde_json::to_string(&ports).unwrap(),
+    )
+    .unwrap();
+
+    // Barbados lists no goods, so it is stocked at the edited prices.
+    let model = Model::with_content(&content);
+    let barbados = &model.ports[&PortLocation::Barbados];
+    for kind in CARGO_KINDS.iter() {
+        let price = market::listing_price(&content, barbados, *kind);
+        assert_eq!(barbados.cargos.get(*kind).price, price);
+        assert!(
+            price
+                > CONTENT.ports[&PortLocation::Barbados]
+                    .cargos
+                    .get(*kind)
+                    .price
+        );
+    }
+    assert!(invariants::check(&model).is_ok());
+}
+
+#[test]
+fn ships_without_enough_cannons_are_refused() {
+    let error = refused("ships.json", |ships| {
+        ship(ships, "Cutter")["cannons"] = (MINIMUM_SHIP_CANNONS - 1).into();
+    });
+    assert_eq!(error.file, "ships.json");
+    assert!(error.reason.starts_with("Cutter"));
+    assert!(error.reason.contains("cannons"));
+}
+
+#[test]
+fn ships_that_cannot_sail_are_refused() {
+    let error = refused("ships.json", |ships| {
+        ship(ships, "Brig")["crew"] = 0.into();
+    });
+    assert!(error.reason.contains("crew"));
+
+    let error = refused("ships.json", |ships| {
+        let junk = ship(ships, "Junk");
+        junk["food"] = (junk["cargos_capacity"].as_i64().unwrap() + 1).into();
+    });
+    assert!(error.reason.contains("food"));
+}
+
+#[test]
+fn missing_and_duplicate_entries_are_refused() {
+    let error = refused("ships.json", |ships| {
+        ships.retain(|s| s["class"] != "Frigate");
+    });
+    assert_eq!(error.reason, "Frigate is missing");
+
+    let error = refused("ports.json", |ports| {
+        let first = ports[0].clone();
+        ports.push(first);
+    });
+    assert_eq!(error.file, "ports.json");
+    assert!(error.reason.ends_with("is listed twice"));
+
+    let error = refused("cargos.json", |cargos| {
+        cargos[0]["base_price"] = 0.into();
+    });
+    assert_eq!(error.file, "cargos.json");
+    assert!(error.reason.contains("base price"));
+}
@@ -0,0 +1,138 @@
+//! Contraband only trades on a black market, and customs officers seize it
+//! from ships coming into port.
+
+use lunar::customs::{self, BLACK_MARKET_PREMIUM};
+use lunar::engine::{self, GameError};
+use lunar::market::{self, Venue};
+use lunar::model::*;
+use lunar::tariffs;
+
+// A location whose port bans `kind` and has a black market, if any.
+fn black_market_for(model: &Model, kind: CargoKind) -> Option<PortLocation> {
+    PORT_LOCATIONS.iter().copied().find(|l| {
+        let port = &model.ports[l];
+        port.black_market && customs::is_contraband(&CONTENT, port, kind)
+    })
+}
+
+fn contraband_and_port(model: &Model) -> (CargoKind, PortLocation) {
+    CARGO_KINDS
+        .iter()
+        .find_map(|&kind| black_market_for(model, kind).map(|l| (kind, l)))
+        .expect("some port has a black market for some contraband")
+}
+
+fn docked_at(l: PortLocation) -> Model {
+    let mut model = Model {
+        current_screen: Screen::DockMarket,
+        current_port_location: l,
+        ..Default::default()
+    };
+    model.player.coins = 100_000;
+    model
+}
+
+#[test]
+fn contraband_only_trades_on_the_black_market() {
+    let (kind, l) = contraband_and_port(&Model::default());
+    let mut model = docked_at(l);
+
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyCargo(l, kind, 1)),
+        Err(GameError::Contraband(kind))
+    );
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyContraband(l, kind, 2)),
+        Ok(())
+    );
+    assert_eq!(
+        engine::apply(&mut model, Msg::SellCargo(l, kind, 1)),
+        Err(GameError::Contraband(kind))
+    );
+    assert_eq!(
+        engine::apply(&mut model, Msg::SellContraband(l, kind, 2)),
+        Ok(())
+    );
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyContraband(l, CargoKind::Food, 1)),
+        Err(GameError::NotContraband(CargoKind::Food))
+    );
+
+    let plain = PORT_LOCATIONS
+        .iter()
+        .copied()
+        .find(|l| !model.ports[l].black_market)
+        .unwrap();
+    let mut model = docked_at(plain);
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyContraband(plain, kind, 1)),
+        Err(GameError::NoBlackMarket(plain))
+    );
+}
+
+#[test]
+fn black_market_trades_at_a_premium() {
+    let (kind, l) = contraband_and_port(&Model::default());
+    let model = docked_at(l);
+    let open = market::quote_at(&model, l, kind, Venue::Market);
+    let black = market::quote_at(&model, l, kind, Venue::BlackMarket);
+
+    assert!(black.sell > open.sell);
+    assert!(black.buy > open.buy);
+    assert!(black.sell >= open.sell * (100 + BLACK_MARKET_PREMIUM) / 100 * 3 / 4);
+}
+
+#[test]
+fn inspections_seize_contraband_and_fine_the_player() {
+    let model = Model::default();
+    let (kind, l) = PORT_LOCATIONS
+        .iter()
+        .flat_map(|&l| CARGO_KINDS.iter().map(move |&kind| (kind, l)))
+        .find(|&(kind, l)| customs::is_contraband(&CONTENT, &model.ports[&l], kind))
+        .unwrap();
+    let from = PORT_LOCATIONS.iter().copied().find(|&p| p != l).unwrap();
+
+    // Some seed gets the ship searched, and every search finds the lot.
+    let mut searched = 0;
+    for seed in 0..200 {
+        let mut model = Model {
+            current_screen: Screen::MainNavigation,
+            current_port_location: from,
+            rng: rand::SeedableRng::seed_from_u64(seed),
+            ..Default::default()
+        };
+        model.player.ship.cargos.get_mut(CargoKind::Food).unit = 20;
+        model.player.ship.cargos.get_mut(kind).unit = 10;
+        // The docking fee is due whether or not the ship is searched.
+        let fee = tariffs::levies(&model.ports[&l], &model.player).docking_fee;
+        let coins = model.player.coins - fee;
+
+        assert_eq!(
+            engine::apply(&mut model, Msg::SwitchPlayerLocation(l)),
+            Ok(())
+        );
+        match model.player.ship.cargos.unit(kind) {
+            0 => {
+                searched += 1;
+                let fine = 10 * CONTENT.cargos[&kind].base_price * customs::FINE_PERCENT / 100;
+                assert_eq!(model.player.coins, coins - fine);
+            }
+            units => {
+                assert_eq!(units, 10);
+                assert_eq!(model.player.coins, coins);
+            }
+        }
+    }
+
+    let chance = customs::inspection_chance(&model.ports[&l]) as i32;
+    assert!(
+        searched > 0 && searched < 200,
+        "searched {} of 200",
+        searched
+    );
+    assert!(
+        (searched - chance * 2).abs() < 40,
+        "searched {} of 200",
+        searched
+    );
+}
@@ -0,0 +1,61 @@
+//! Every roll comes from the seeded rng kept in the model, so the same seed
+//! and the same messages always play out the same game.
+
+use lunar::engine;
+use lunar::model::*;
+use lunar::simulation::{RandomPlayer, Strategy};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn new_game(seed: u64) -> Model {
+    let mut model = Model::default();
+    model.reseed(seed);
+    model.current_screen = Screen::MainNavigation;
+    model
+}
+
+// Lets a bot play, and returns what it sent along with the resulting game.
+fn play(seed: u64, actions: usize) -> (Vec<Msg>, Model) {
+    let mut model = new_game(seed);
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut msgs = Vec::new();
+    for _ in 0..actions {
+        let msg = RandomPlayer.next_msg(&model, &mut rng);
+        let _ = engine::apply(&mut model, msg.to_owned());
+        msgs.push(msg);
+    }
+    (msgs, model)
+}
+
+#[test]
+fn same_seed_and_messages_give_identical_games() {
+    for seed in [0, 1, 42, u64::MAX] {
+        let (msgs, played) = play(seed, 500);
+
+        let mut replayed = new_game(seed);
+        for msg in msgs {
+            let _ = engine::apply(&mut replayed, msg);
+        }
+        assert!(replayed == played);
+        assert_eq!(
+            serde_json::to_string(&replayed).unwrap(),
+            serde_json::to_string(&played).unwrap()
+        );
+    }
+}
+
+#[test]
+fn rng_state_survives_a_save_and_load() {
+    let (msgs, played) = play(7, 500);
+    let (head, tail) = msgs.split_at(250);
+
+    let mut model = new_game(7);
+    for msg in head {
+        let _ = engine::apply(&mut model, msg.to_owned());
+    }
+    let mut model: Model = serde_json::from_str(&serde_json::to_string(&model).unwrap()).unwrap();
+    for msg in tail {
+        let _ = engine::apply(&mut model, msg.to_owned());
+    }
+    assert!(model == played);
+}
@@ -0,0 +1,133 @@
+//! The engine on its own, without any frontend: every message changes the
+//! game as described. Refusals are covered in `errors.rs`.
+
+use lunar::engine::{self, GameError};
+use lunar::model::*;
+
+fn at_sea() -> Model {
+    Model {
+        current_screen: Screen::MainNavigation,
+        rng: rand::SeedableRng::seed_from_u64(3),
+        ..Default::default()
+    }
+}
+
+fn elsewhere(model: &Model) -> PortLocation {
+    PORT_LOCATIONS
+        .iter()
+        .copied()
+        .find(|&l| l != model.current_port_location)
+        .unwrap()
+}
+
+#[test]
+fn character_creation_sets_the_player_and_ship() {
+    let mut model = Model::default();
+    for msg in [
+        Msg::SetPlayerName(String::from("Anne")),
+        Msg::SetPlayerAge(30),
+        Msg::SetPlayerNationality(Nationality::French),
+        Msg::SetShipName(String::from("Revenge")),
+        Msg::SetShipClass(ShipClass::Brig),
+    ] {
+        assert_eq!(engine::apply(&mut model, msg), Ok(()));
+    }
+
+    assert_eq!(model.player.name, "Anne");
+    assert_eq!(model.player.age, 30);
+    assert_eq!(model.player.nationality, Nationality::French);
+    assert_eq!(model.player.ship.class, ShipClass::Brig);
+    assert_eq!(model.player.ship.name, "Revenge");
+}
+
+#[test]
+fn sailing_takes_days_and_needs_food() {
+    let mut model = at_sea();
+    let to = elsewhere(&model);
+    let date = model.date;
+    model.player.ship.cargos.get_mut(CargoKind::Food).unit = 20;
+
+    assert_eq!(
+        engine::apply(&mut model, Msg::SwitchPlayerLocation(to)),
+        Ok(())
+    );
+    assert_eq!(model.current_port_location, to);
+    assert!((1..=9).contains(&(model.date - date).num_days()));
+    assert!(model.player.ship.cargos.unit(CargoKind::Food) <= 20);
+
+    let to = elsewhere(&model);
+    model.player.ship.cargos.get_mut(CargoKind::Food).unit = 0;
+    assert_eq!(
+        engine::apply(&mut model, Msg::SwitchPlayerLocation(to)),
+        Ok(())
+    );
+    assert_eq!(
+        model.current_screen,
+        Screen::GameLost(GameLostReason::FoodMutiny)
+    );
+    assert!(model.player == Model::default().player);
+}
+
+#[test]
+fn repairs_and_hires_are_paid_in_full_or_refused() {
+    let mut model = at_sea();
+    model.player.ship.hull -= 4;
+    model.player.ship.crew -= 5;
+    let repair = model.player.ship.cost_to_repair();
+    let hire = model.player.ship.cost_to_hire();
+    assert!(repair > 0 && hire > 0);
+
+    assert_eq!(
+        engine::apply(&mut model, Msg::RepairShip(repair - 1)),
+        Err(GameError::InsufficientFunds {
+            needed: repair,
+            available: repair - 1
+        })
+    );
+    let coins = model.player.coins;
+    assert_eq!(engine::apply(&mut model, Msg::RepairShip(coins)), Ok(()));
+    assert_eq!(engine::apply(&mut model, Msg::HireCrew(coins)), Ok(()));
+    assert_eq!(model.player.coins, coins - repair - hire);
+    assert_eq!(model.player.ship.hull, model.player.ship.hull_capacity);
+    assert_eq!(model.player.ship.crew, model.player.ship.crew_capacity);
+}
+
+#[test]
+fn confirmations_hold_a_message_until_answered() {
+    let mut model = at_sea();
+    let ask = || {
+        Msg::AskConfirmation(
+            Text::notice(String::from("Rename?")),
+            Box::new(Msg::SetPlayerName(String::from("Jack"))),
+        )
+    };
+
+    assert_eq!(engine::apply(&mut model, ask()), Ok(()));
+    assert_eq!(model.player.name, "Player");
+    assert_eq!(engine::apply(&mut model, Msg::CancelConfirmation), Ok(()));
+    assert!(model.pending_confirmation.is_none());
+    assert_eq!(
+        engine::apply(&mut model, Msg::Confirm),
+        Err(GameError::NothingToConfirm)
+    );
+
+    assert_eq!(engine::apply(&mut model, ask()), Ok(()));
+    assert_eq!(engine::apply(&mut model, Msg::Confirm), Ok(()));
+    assert_eq!(model.player.name, "Jack");
+    assert!(model.pending_confirmation.is_none());
+}
+
+#[test]
+fn toasts_only_show_what_the_last_message_logged() {
+    let mut model = at_sea();
+    let notify = |text: &str| Msg::Notify(Severity::Info, Text::notice(String::from(text)));
+
+    assert_eq!(engine::apply(&mut model, notify("one")), Ok(()));
+    assert_eq!(engine::apply(&mut model, notify("two")), Ok(()));
+    assert_eq!(model.toasts().len(), 1);
+    assert_eq!(model.toasts()[0].text, Text::notice(String::from("two")));
+
+    assert_eq!(engine::apply(&mut model, Msg::DismissToasts), Ok(()));
+    assert!(model.toasts().is_empty());
+    assert_eq!(model.log.len(), 2);
+}
@@ -0,0 +1,141 @@
+//! Every refused action says exactly why, logs that reason, and leaves the
+//! game itself as it was.
+
+use lunar::engine::{self, GameError};
+use lunar::market::{self, Venue};
+use lunar::model::*;
+use lunar::tariffs;
+
+fn docked() -> Model {
+    Model {
+        current_screen: Screen::DockMarket,
+        rng: rand::SeedableRng::seed_from_u64(3),
+        ..Default::default()
+    }
+}
+
+// Compares everything but the log, which also records refusals.
+fn same_game(a: &Model, b: &Model) -> bool {
+    a.date == b.date
+        && a.current_screen == b.current_screen
+        && a.player == b.player
+        && a.ports == b.ports
+        && a.enemy == b.enemy
+        && a.rng == b.rng
+}
+
+fn assert_refused(model: &mut Model, msg: Msg, error: GameError) {
+    let before = model.clone();
+    assert_eq!(engine::apply(model, msg), Err(error.clone()));
+    assert_eq!(model.log.len(), before.log.len() + 1);
+    assert_eq!(model.log.last().unwrap().text, error.text());
+    assert!(same_game(model, &before));
+}
+
+#[test]
+fn refused_messages_are_logged_and_change_nothing_else() {
+    let mut model = docked();
+    let here = model.current_port_location;
+
+    for (msg, error) in [
+        (Msg::SetPlayerAge(12), GameError::InvalidAge(12)),
+        (
+            Msg::SwitchPlayerLocation(here),
+            GameError::AlreadyInPort(here),
+        ),
+        (Msg::Confirm, GameError::NothingToConfirm),
+    ] {
+        assert_refused(&mut model, msg, error);
+    }
+}
+
+#[test]
+fn fighting_needs_an_enemy() {
+    let mut model = docked();
+    for msg in [
+        Msg::SkirmishChaseClose,
+        Msg::SkirmishChaseDistant,
+        Msg::SkirmishChaseBroadside,
+        Msg::SkirmishBattleSwingSword,
+        Msg::SkirmishBattleShootFalconet,
+        Msg::TakeEnemyCargo(CargoKind::Food),
+    ] {
+        assert_refused(&mut model, msg, GameError::NotInCombat);
+    }
+}
+
+#[test]
+fn trades_say_what_is_missing() {
+    let mut model = docked();
+    let l = model.current_port_location;
+    let stock = model.ports[&l].cargos.unit(CargoKind::Wood);
+    let held = model.player.ship.cargos.unit(CargoKind::Food);
+
+    let price = market::buy_cost(&model, l, CargoKind::Wood, Venue::Market, 1);
+    let needed = price + tariffs::export_duty(&model, l, Venue::Market, price);
+    model.player.coins = needed - 1;
+    assert_refused(
+        &mut model,
+        Msg::BuyCargo(l, CargoKind::Wood, 1),
+        GameError::InsufficientFunds {
+            needed,
+            available: needed - 1,
+        },
+    );
+
+    model.player.coins = 1_000_000;
+    model.player.ship.cargos_capacity = model.player.ship.cargos.total_unit();
+    assert_refused(
+        &mut model,
+        Msg::BuyCargo(l, CargoKind::Wood, 1),
+        GameError::CargoHoldFull { room: 0 },
+    );
+    assert_refused(
+        &mut model,
+        Msg::BuyCargo(l, CargoKind::Wood, stock + 1),
+        GameError::PortOutOfStock {
+            kind: CargoKind::Wood,
+            available: stock,
+        },
+    );
+    assert_refused(
+        &mut model,
+        Msg::SellCargo(l, CargoKind::Food, held + 1),
+        GameError::NothingToSell {
+            kind: CargoKind::Food,
+            available: held,
+        },
+    );
+    assert_refused(
+        &mut model,
+        Msg::SellCargo(l, CargoKind::Food, -1),
+        GameError::InvalidQuantity(-1),
+    );
+}
+
+#[test]
+fn repairs_and_hires_say_what_they_cost() {
+    let mut model = docked();
+    model.player.ship.hull -= 4;
+    model.player.ship.crew -= 5;
+    let repair = model.player.ship.cost_to_repair();
+    let hire = model.player.ship.cost_to_hire();
+
+    assert_refused(
+        &mut model,
+        Msg::RepairShip(repair - 1),
+        GameError::InsufficientFunds {
+            needed: repair,
+            available: repair - 1,
+        },
+    );
+    model.player.coins = hire - 1;
+    assert_refused(
+        &mut model,
+        Msg::HireCrew(hire),
+        GameError::InsufficientFunds {
+            needed: hire,
+            available: hire - 1,
+        },
+    );
+}
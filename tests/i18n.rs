@@ -0,0 +1,80 @@
+//! Every shipped locale has to translate every key, so that no player ever
+//! sees a mix of languages.
+
+use lunar::i18n::*;
+use lunar::model::*;
+
+#[test]
+fn no_locale_misses_a_key() {
+    for locale in LOCALES.iter() {
+        assert_eq!(missing_keys(*locale), Vec::<String>::new(), "{:?}", locale);
+    }
+}
+
+#[test]
+fn missing_keys_fall_back_to_english_then_the_key() {
+    assert_eq!(tr(Locale::French, "common.back"), "Retour");
+    assert_eq!(
+        Locale::French.fallbacks(),
+        vec![Locale::French, Locale::English]
+    );
+    assert_eq!(
+        tr(Locale::English, "port.Nassau.description"),
+        CONTENT.ports[&PortLocation::Nassau].description
+    );
+    assert_eq!(tr(Locale::French, "no.such.key"), "no.such.key");
+    assert_eq!(
+        tr_with(Locale::English, "saves.voyage", &[("captain", &"Anne")]),
+        "Anne's voyage"
+    );
+}
+
+#[test]
+fn the_log_is_shown_in_the_chosen_language() {
+    let mut model = Model {
+        current_screen: Screen::MainNavigation,
+        ..Default::default()
+    };
+    model.player.ship.cargos.get_mut(CargoKind::Food).unit = 30;
+    lunar::engine::apply(&mut model, Msg::SwitchPlayerLocation(PortLocation::Nassau)).unwrap();
+    let arrived = &model.log[0].text;
+    assert_eq!(arrived.key, "log.arrived");
+    assert!(render(Locale::English, arrived).starts_with("Arrived at Nassau after"));
+    assert!(render(Locale::French, arrived).starts_with("Arrivée à Nassau après"));
+
+    // Refusals are logged the same way, and read in English elsewhere.
+    let error = lunar::engine::apply(&mut model, Msg::SwitchPlayerLocation(PortLocation::Nassau))
+        .unwrap_err();
+    assert_eq!(model.log.last().unwrap().text, error.text());
+    assert_eq!(error.to_string(), "You are already docked at Nassau.");
+    assert_eq!(
+        render(Locale::French, &error.text()),
+        "Vous êtes déjà à quai à Nassau."
+    );
+}
+
+#[test]
+fn frontend_notices_are_shown_in_the_chosen_language() {
+    let error = lunar::transfer::import("{").err().unwrap();
+    assert!(error.to_string().starts_with("Cannot read the save: "));
+    assert!(
+        render(Locale::French, &error.text()).starts_with("Impossible de lire la sauvegarde : ")
+    );
+
+    let mut model = Model::default();
+    let question = Text::new("shipyard.buy_question").text("class", ShipClass::Brig.text());
+    let msg = Msg::AskConfirmation(question, Box::new(Msg::BuyAndReplaceShip(ShipClass::Brig)));
+    lunar::engine::apply(&mut model, msg).unwrap();
+    let question = &model.pending_confirmation.unwrap().question;
+    assert_eq!(
+        render(Locale::English, question),
+        "Are you sure you want to buy this Brig?"
+    );
+    assert_eq!(
+        render(Locale::French, question),
+        format!(
+            "Voulez-vous vraiment acheter ce navire ({}) ?",
+            ShipClass::Brig.name(Locale::French)
+        )
+    );
+}
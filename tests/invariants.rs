@@ -0,0 +1,82 @@
+//! Throws random sequences of messages at the engine and checks that every
+//! model along the way still follows `invariants::check`.
+
+use lunar::transitions::SCREENS;
+use lunar::{engine, invariants, model::*, simulation};
+use proptest::prelude::*;
+
+fn location() -> impl Strategy<Value = PortLocation> {
+    (0..PORT_LOCATIONS.len()).prop_map(|i| PORT_LOCATIONS[i])
+}
+
+fn kind() -> impl Strategy<Value = CargoKind> {
+    (0..CARGO_KINDS.len()).prop_map(|i| CARGO_KINDS[i])
+}
+
+fn class() -> impl Strategy<Value = ShipClass> {
+    (0..SHIP_CLASSES.len()).prop_map(|i| SHIP_CLASSES[i])
+}
+
+fn plain_msg() -> impl Strategy<Value = Msg> {
+    prop_oneof![
+        Just(Msg::ResetModel),
+        ".*".prop_map(Msg::SetPlayerName),
+        any::<i8>().prop_map(Msg::SetPlayerAge),
+        (0..NATIONALITIES.len()).prop_map(|i| Msg::SetPlayerNationality(NATIONALITIES[i].clone())),
+        ".*".prop_map(Msg::SetShipName),
+        class().prop_map(Msg::SetShipClass),
+        any::<u64>().prop_map(Msg::SetSeed),
+        (0..SCREENS.len()).prop_map(|i| Msg::SwitchScreen(SCREENS[i].clone())),
+        location().prop_map(Msg::SwitchPlayerLocation),
+        (location(), kind(), -5..300).prop_map(|(l, k, n)| Msg::BuyCargo(l, k, n)),
+        (location(), kind(), -5..300).prop_map(|(l, k, n)| Msg::SellCargo(l, k, n)),
+        (location(), kind(), -5..300).prop_map(|(l, k, n)| Msg::BuyContraband(l, k, n)),
+        (location(), kind(), -5..300).prop_map(|(l, k, n)| Msg::SellContraband(l, k, n)),
+        Just(Msg::SkirmishChaseClose),
+        Just(Msg::SkirmishChaseDistant),
+        Just(Msg::SkirmishChaseBroadside),
+        Just(Msg::SkirmishBattleSwingSword),
+        Just(Msg::SkirmishBattleShootFalconet),
+        any::<i32>().prop_map(Msg::RepairShip),
+        any::<i32>().prop_map(Msg::HireCrew),
+        (-5..20_000).prop_map(Msg::TakeLoan),
+        any::<i32>().prop_map(Msg::RepayLoan),
+        kind().prop_map(Msg::TakeEnemyCargo),
+        class().prop_map(Msg::BuyAndReplaceShip),
+        ".*".prop_map(|text| Msg::Notify(Severity::Info, Text::notice(text))),
+        Just(Msg::DismissToasts),
+        Just(Msg::Confirm),
+        Just(Msg::CancelConfirmation),
+    ]
+}
+
+fn msg() -> impl Strategy<Value = Msg> {
+    prop_oneof![
+        9 => plain_msg(),
+        1 => plain_msg().prop_map(|m| Msg::AskConfirmation(Text::notice(String::from("Sure?")), Box::new(m))),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn random_messages_keep_invariants(seed in any::<u64>(), msgs in prop::collection::vec(msg(), 0..200)) {
+        let mut model = Model::default();
+        let _ = engine::apply(&mut model, Msg::SetSeed(seed));
+
+        for msg in msgs {
+            let shown = format!("{:?}", msg);
+            let _ = engine::apply(&mut model, msg);
+            prop_assert_eq!(invariants::check(&model), Ok(()), "after {}", shown);
+        }
+    }
+}
+
+#[test]
+fn bots_keep_invariants() {
+    // `engine::apply` panics on a broken invariant in debug builds.
+    for (_, new_strategy) in simulation::STRATEGIES {
+        for seed in 0..20 {
+            simulation::play(new_strategy, seed, 500);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! A recorded journal replays into the very same game, and a journal that was
+//! tampered with is caught at the step where it stops matching.
+
+use chrono::Duration;
+use lunar::engine;
+use lunar::journal::{self, Journal};
+use lunar::model::*;
+use lunar::simulation::{RandomPlayer, Strategy};
+use lunar::transfer;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// Plays like the web frontend does: every message is recorded once applied.
+fn record(seed: u64, actions: usize) -> (Journal, Model) {
+    let mut model = Model::default();
+    model.reseed(seed);
+    let journal = Journal::new(seed);
+    play_on(model, journal, seed, actions)
+}
+
+fn play_on(mut model: Model, mut journal: Journal, seed: u64, actions: usize) -> (Journal, Model) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    for _ in 0..actions {
+        let msg = RandomPlayer.next_msg(&model, &mut rng);
+        let _ = engine::apply(&mut model, msg.to_owned());
+        journal.record(msg, &model);
+    }
+    (journal, model)
+}
+
+#[test]
+fn recorded_games_replay_identically() {
+    for seed in [1, 9, 2022] {
+        let (journal, played) = record(seed, 300);
+        let exported = journal.to_json().unwrap();
+        let imported = serde_json::from_str::<Journal>(&exported).unwrap();
+
+        let replayed = journal::replay(&imported).ok().unwrap();
+        assert!(replayed == played);
+        assert_eq!(journal::digest(&replayed), journal::digest(&played));
+    }
+}
+
+#[test]
+fn games_picked_up_midway_replay_from_where_they_started() {
+    let (_, played) = record(7, 200);
+    let imported = transfer::import(&transfer::export_json(&played)).unwrap();
+
+    let (journal, played) = play_on(imported, Journal::starting_from(&played), 8, 200);
+    let exported = journal.to_json().unwrap();
+    let imported = serde_json::from_str::<Journal>(&exported).unwrap();
+    let replayed = journal::replay(&imported).ok().unwrap();
+    assert!(replayed == played);
+
+    // Replaying from a new game would not get past the first entry.
+    let fresh = Journal {
+        start: None,
+        ..journal
+    };
+    assert_eq!(journal::replay(&fresh).err().unwrap().step, 0);
+}
+
+#[test]
+fn tampered_entries_are_caught_even_on_the_same_date_and_screen() {
+    let mut model = Model::default();
+    model.reseed(5);
+    let mut journal = Journal::new(5);
+    for msg in [
+        Msg::SetPlayerName(String::from("Anne")),
+        Msg::SetPlayerAge(30),
+        Msg::SetShipClass(ShipClass::Brig),
+    ] {
+        engine::apply(&mut model, msg.to_owned()).unwrap();
+        journal.record(msg, &model);
+    }
+    assert!(journal::replay(&journal).is_ok());
+
+    journal.entries[1].msg = Msg::SetPlayerAge(40);
+    let divergence = journal::replay(&journal).err().unwrap();
+    assert_eq!(divergence.step, 1);
+    assert!(divergence.state_differs);
+    assert_eq!(divergence.replayed_date, divergence.recorded_date);
+
+    // Entries from before digests were kept are checked as they used to be.
+    journal.entries[1].digest = None;
+    journal.entries[2].digest = None;
+    assert!(journal::replay(&journal).is_ok());
+}
+
+#[test]
+fn a_changed_date_is_reported_as_such() {
+    let (mut journal, _) = record(3, 100);
+    let step = journal
+        .entries
+        .iter()
+        .position(|e| matches!(e.msg, Msg::SwitchPlayerLocation(_)))
+        .unwrap();
+    journal.entries[step].date += Duration::days(1);
+
+    let divergence = journal::replay(&journal).err().unwrap();
+    assert_eq!(divergence.step, step);
+    assert!(!divergence.state_differs);
+    assert!(divergence.replayed_date != divergence.recorded_date);
+}
@@ -0,0 +1,138 @@
+//! Loans are limited by net worth and compound as the days go by, and every
+//! missed due date is handled worse than the last one.
+
+use chrono::Duration;
+use lunar::engine::{self, GameError};
+use lunar::lending;
+use lunar::model::*;
+
+fn in_tavern() -> Model {
+    Model {
+        current_screen: Screen::TavernMoneylender,
+        rng: rand::SeedableRng::seed_from_u64(7),
+        ..Default::default()
+    }
+}
+
+// Sails somewhere else with enough food to get there.
+fn sail(model: &mut Model) {
+    sail_with_food(model, 20);
+}
+
+fn sail_with_food(model: &mut Model, food: i32) {
+    let to = PORT_LOCATIONS
+        .iter()
+        .copied()
+        .find(|&l| l != model.current_port_location)
+        .unwrap();
+    model.current_screen = Screen::MainNavigation;
+    model.enemy = None;
+    model.player.ship.cargos.get_mut(CargoKind::Food).unit = food;
+    assert_eq!(engine::apply(model, Msg::SwitchPlayerLocation(to)), Ok(()));
+}
+
+// Owes `owed` coins due yesterday, with no coins to pay them.
+fn overdue(owed: i32) -> Model {
+    let mut model = in_tavern();
+    let mut loan = lending::new_loan(&model, owed);
+    loan.due = model.date - Duration::days(1);
+    model.player.loan = Some(loan);
+    model.player.coins = 0;
+    model
+}
+
+#[test]
+fn loans_are_limited_by_net_worth() {
+    let mut model = in_tavern();
+    let limit = lending::credit_limit(&model);
+    assert!(limit > 0 && limit < lending::net_worth(&model));
+
+    assert_eq!(
+        engine::apply(&mut model, Msg::TakeLoan(limit + 1)),
+        Err(GameError::LoanTooLarge { limit })
+    );
+    assert_eq!(
+        engine::apply(&mut model, Msg::TakeLoan(0)),
+        Err(GameError::InvalidAmount(0))
+    );
+    let coins = model.player.coins;
+    assert_eq!(engine::apply(&mut model, Msg::TakeLoan(limit)), Ok(()));
+    assert_eq!(model.player.coins, coins + limit);
+    assert_eq!(
+        engine::apply(&mut model, Msg::TakeLoan(1)),
+        Err(GameError::LoanOutstanding { owed: limit })
+    );
+}
+
+#[test]
+fn interest_compounds_over_the_days_at_sea() {
+    let mut model = in_tavern();
+    assert_eq!(engine::apply(&mut model, Msg::TakeLoan(1_000)), Ok(()));
+    let taken = model.date;
+
+    sail(&mut model);
+    sail(&mut model);
+    let days = (model.date - taken).num_days();
+    let loan = model.player.loan.as_ref().unwrap();
+    assert_eq!(loan.owed, lending::compound(1_000, days));
+    assert!(loan.owed > 1_000);
+    assert_eq!(lending::compound(1_000, 0), 1_000);
+}
+
+#[test]
+fn repaying_more_than_owed_only_pays_off_the_loan() {
+    let mut model = in_tavern();
+    assert_eq!(
+        engine::apply(&mut model, Msg::RepayLoan(10)),
+        Err(GameError::NoLoan)
+    );
+    assert_eq!(engine::apply(&mut model, Msg::TakeLoan(500)), Ok(()));
+    let coins = model.player.coins;
+
+    assert_eq!(engine::apply(&mut model, Msg::RepayLoan(200)), Ok(()));
+    assert_eq!(model.player.loan.as_ref().unwrap().owed, 300);
+    assert_eq!(engine::apply(&mut model, Msg::RepayLoan(10_000)), Ok(()));
+    assert!(model.player.loan.is_none());
+    assert_eq!(model.player.coins, coins - 500);
+}
+
+#[test]
+fn overdue_loans_are_collected_from_coins_first() {
+    let mut model = overdue(1_000);
+    model.player.coins = 5_000;
+    let before = model.player.coins;
+
+    sail(&mut model);
+    assert!(model.player.loan.is_none());
+    assert!(model.player.coins < before - 1_000);
+    assert_eq!(model.current_screen, Screen::MainNavigation);
+}
+
+#[test]
+fn missed_due_dates_escalate_to_bankruptcy() {
+    let mut model = overdue(1_000_000);
+
+    sail(&mut model);
+    let loan = model.player.loan.clone().unwrap();
+    assert_eq!(loan.missed, 1);
+    assert!(loan.due > model.date);
+    assert_eq!(model.current_screen, Screen::SkirmishChase);
+    assert!(model.enemy.is_some());
+
+    let class = model.player.ship.class;
+    // Arriving with the last of the food, which may spoil on the way.
+    model.player.loan.as_mut().unwrap().due = model.date - Duration::days(1);
+    sail_with_food(&mut model, MINIMUM_PLAYER_FOOD.into());
+    assert_eq!(model.player.loan.as_ref().unwrap().missed, 2);
+    assert!(model.player.ship.class != class);
+    assert!(model.player.ship.price < CONTENT.ships[&class].price);
+    assert!(model.player.ship.cargos.unit(CargoKind::Food) >= MINIMUM_PLAYER_FOOD.into());
+
+    model.player.loan.as_mut().unwrap().due = model.date - Duration::days(1);
+    sail(&mut model);
+    assert_eq!(
+        model.current_screen,
+        Screen::GameLost(GameLostReason::Bankruptcy)
+    );
+    assert!(model.player.loan.is_none());
+}
@@ -0,0 +1,304 @@
+//! Bulk trades cost the same as trading unit by unit, and go through whole or
+//! not at all. Price histories keep one point a day within their window, and
+//! ports keep more of what they produce than of what they demand. Prices rise
+//! as stock runs low and drift back as it recovers, and every port buys for
+//! less than it sells.
+
+use lunar::customs;
+use lunar::engine::{self, GameError};
+use lunar::market::{self, Venue};
+use lunar::model::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn docked() -> Model {
+    Model {
+        current_screen: Screen::DockMarket,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn bulk_trades_cost_the_same_as_single_units() {
+    let model = docked();
+    let l = model.current_port_location;
+    let legal = CARGO_KINDS
+        .iter()
+        .filter(|&&kind| !customs::is_contraband(&CONTENT, &model.ports[&l], kind));
+    for &kind in legal {
+        let mut bulk = docked();
+        let mut single = docked();
+        bulk.player.coins = 100_000;
+        single.player.coins = 100_000;
+        let units = market::max_buy(&bulk, l, kind, Venue::Market).min(20);
+
+        assert_eq!(
+            engine::apply(&mut bulk, Msg::BuyCargo(l, kind, units)),
+            Ok(())
+        );
+        for _ in 0..units {
+            assert_eq!(
+                engine::apply(&mut single, Msg::BuyCargo(l, kind, 1)),
+                Ok(())
+            );
+        }
+        assert_eq!(bulk.player.coins, single.player.coins, "buying {}", kind);
+        assert!(bulk.ports == single.ports);
+
+        assert_eq!(
+            engine::apply(&mut bulk, Msg::SellCargo(l, kind, units)),
+            Ok(())
+        );
+        for _ in 0..units {
+            assert_eq!(
+                engine::apply(&mut single, Msg::SellCargo(l, kind, 1)),
+                Ok(())
+            );
+        }
+        assert_eq!(bulk.player.coins, single.player.coins, "selling {}", kind);
+        assert!(bulk.ports == single.ports);
+    }
+}
+
+#[test]
+fn max_buy_is_affordable_and_one_more_is_not() {
+    let mut model = docked();
+    let l = model.current_port_location;
+    model.player.coins = 500;
+    let most = market::max_buy(&model, l, CargoKind::Sugar, Venue::Market);
+
+    assert!(market::buy_cost(&model, l, CargoKind::Sugar, Venue::Market, most) <= 500);
+    assert!(matches!(
+        engine::apply(&mut model, Msg::BuyCargo(l, CargoKind::Sugar, most + 1)),
+        Err(GameError::InsufficientFunds { .. }) | Err(GameError::CargoHoldFull { .. })
+    ));
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyCargo(l, CargoKind::Sugar, most)),
+        Ok(())
+    );
+}
+
+#[test]
+fn refused_lots_leave_the_game_untouched() {
+    let model = docked();
+    let l = model.current_port_location;
+    let held = model.player.ship.cargos.unit(CargoKind::Food);
+    let stock = model.ports[&l].cargos.unit(CargoKind::Wood);
+
+    for (msg, error) in [
+        (
+            Msg::BuyCargo(l, CargoKind::Wood, 0),
+            GameError::InvalidQuantity(0),
+        ),
+        (
+            Msg::BuyCargo(l, CargoKind::Wood, stock + 1),
+            GameError::PortOutOfStock {
+                kind: CargoKind::Wood,
+                available: stock,
+            },
+        ),
+        (
+            Msg::SellCargo(l, CargoKind::Food, held + 1),
+            GameError::NothingToSell {
+                kind: CargoKind::Food,
+                available: held,
+            },
+        ),
+    ] {
+        let mut after = model.clone();
+        after.player.coins = 1_000_000;
+        let before = after.clone();
+
+        assert_eq!(engine::apply(&mut after, msg), Err(error));
+        assert!(after.player == before.player);
+        assert!(after.ports == before.ports);
+    }
+
+    let mut after = model.clone();
+    after.player.coins = 1_000_000;
+    after.player.ship.cargos_capacity = after.player.ship.cargos.total_unit() + 2;
+    let before = after.clone();
+
+    assert_eq!(
+        engine::apply(&mut after, Msg::BuyCargo(l, CargoKind::Wood, 3)),
+        Err(GameError::CargoHoldFull { room: 2 })
+    );
+    assert!(after.player == before.player);
+    assert!(after.ports == before.ports);
+}
+
+#[test]
+fn price_history_keeps_one_point_a_day_within_the_window() {
+    let mut model = docked();
+    let l = model.current_port_location;
+    model.player.coins = 100_000;
+
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyCargo(l, CargoKind::Wood, 2)),
+        Ok(())
+    );
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyCargo(l, CargoKind::Wood, 3)),
+        Ok(())
+    );
+    let wood = &model.ports[&l].history[&CargoKind::Wood];
+    assert_eq!(wood.len(), 1);
+    assert_eq!(
+        wood.back().unwrap().unit,
+        model.ports[&l].cargos.unit(CargoKind::Wood)
+    );
+    assert_eq!(
+        wood.back().unwrap().price,
+        model.ports[&l].cargos.get(CargoKind::Wood).price
+    );
+
+    model.current_screen = Screen::MainNavigation;
+    for i in 0..(MAXIMUM_PRICE_HISTORY * 2) {
+        let next = PORT_LOCATIONS[i % PORT_LOCATIONS.len()];
+        let _ = engine::apply(&mut model, Msg::SwitchPlayerLocation(next));
+        model.player.ship.cargos.get_mut(CargoKind::Food).unit = 20;
+    }
+    for port in model.ports.values() {
+        for points in port.history.values() {
+            assert_eq!(points.len(), MAXIMUM_PRICE_HISTORY);
+            assert_eq!(points.back().unwrap().date, model.date);
+            assert!(points
+                .iter()
+                .zip(points.iter().skip(1))
+                .all(|(a, b)| a.date < b.date));
+        }
+    }
+}
+
+#[test]
+fn producers_stock_more_for_less_than_ports_that_demand() {
+    for port in CONTENT.ports.values() {
+        for &kind in CARGO_KINDS.iter() {
+            let info = &CONTENT.cargos[&kind];
+            let cargo = port.cargos.get(kind);
+            assert!(
+                port.cargos.iter().any(|c| c.kind == kind),
+                "{} unlisted",
+                kind
+            );
+
+            match market::role(&CONTENT, port, kind) {
+                market::Role::Produces => {
+                    assert!(info.produced_by.contains(&port.nationality));
+                    assert!(cargo.unit > info.target_units * port.population.market_size() / 100);
+                }
+                market::Role::Demands => {
+                    assert!(cargo.unit < info.target_units * port.population.market_size() / 100);
+                }
+                market::Role::Trades => (),
+            }
+        }
+    }
+}
+
+#[test]
+fn prices_follow_stock_around_the_reference() {
+    let (reference, target) = (40, 100);
+    assert_eq!(market::price_at(reference, target, target), reference);
+    assert_eq!(market::price_at(reference, target, 0), reference * 4);
+    assert_eq!(
+        market::price_at(reference, target, target * 3),
+        reference / 4
+    );
+    assert!((0..400)
+        .map(|stock| market::price_at(reference, target, stock))
+        .collect::<Vec<i32>>()
+        .windows(2)
+        .all(|w| w[0] >= w[1]));
+}
+
+#[test]
+fn trades_move_prices_and_time_brings_them_back() {
+    let mut model = docked();
+    let l = model.current_port_location;
+    let kind = CargoKind::Wood;
+    let price = |model: &Model| model.ports[&l].cargos.get(kind).price;
+    let reference = market::reference_price(&CONTENT, l, kind);
+    model.player.coins = 100_000;
+    model.player.ship.cargos_capacity = 1_000;
+    let listed = price(&model);
+
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyCargo(l, kind, 10)),
+        Ok(())
+    );
+    assert!(price(&model) > listed);
+    assert_eq!(
+        engine::apply(&mut model, Msg::SellCargo(l, kind, 10)),
+        Ok(())
+    );
+    assert_eq!(price(&model), listed);
+
+    // Dumping a hold full of it crashes the price.
+    model.player.ship.cargos.get_mut(kind).unit = 500;
+    assert_eq!(
+        engine::apply(&mut model, Msg::SellCargo(l, kind, 500)),
+        Ok(())
+    );
+    let crashed = price(&model);
+    assert!(crashed < listed);
+
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+    market::drift(&CONTENT, &mut model.ports, &mut rng, 90);
+    assert!(price(&model) > crashed);
+    assert!((price(&model) - reference).abs() < (crashed - reference).abs());
+}
+
+#[test]
+fn every_port_buys_for_less_than_it_sells() {
+    let model = Model::default();
+    for (&l, port) in model.ports.iter() {
+        for cargo in port.cargos.iter() {
+            let quote = market::quote(&model, l, cargo.kind);
+            assert!(quote.buy > quote.sell, "{} at {:?}", cargo.kind, l);
+            assert!(quote.sell <= cargo.price && cargo.price <= quote.buy);
+        }
+    }
+
+    // Buying and selling right back always loses money.
+    let mut model = docked();
+    let l = model.current_port_location;
+    let coins = model.player.coins;
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyCargo(l, CargoKind::Wood, 5)),
+        Ok(())
+    );
+    assert_eq!(
+        engine::apply(&mut model, Msg::SellCargo(l, CargoKind::Wood, 5)),
+        Ok(())
+    );
+    assert!(model.player.coins < coins);
+}
+
+#[test]
+fn spreads_are_thinner_in_busy_ports_and_wider_for_foreigners() {
+    let player = Player {
+        nationality: Nationality::Spanish,
+        ..Default::default()
+    };
+    let port = |population, nationality| Port {
+        population,
+        nationality,
+        ..Default::default()
+    };
+
+    let spreads = [
+        PortPopulation::Small,
+        PortPopulation::Medium,
+        PortPopulation::Large,
+        PortPopulation::Huge,
+    ]
+    .map(|population| market::spread(&port(population, Nationality::Spanish), &player));
+    assert!(spreads.windows(2).all(|w| w[0] > w[1]), "{:?}", spreads);
+
+    for population in [PortPopulation::Small, PortPopulation::Huge] {
+        let home = market::spread(&port(population.clone(), Nationality::Spanish), &player);
+        let abroad = market::spread(&port(population, Nationality::British), &player);
+        assert!(abroad > home);
+    }
+}
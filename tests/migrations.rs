@@ -0,0 +1,272 @@
+//! Saves written by every earlier version of the game upgrade to a valid
+//! current game, and a save that cannot be upgraded is kept for recovery.
+//!
+//! `tests/fixtures/saves/v<N>.json` is a game saved by the version that wrote
+//! schema `N`, with the captain renamed and some coins spent.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use lunar::i18n::{self, Locale};
+use lunar::invariants;
+use lunar::journal::JournalEntry;
+use lunar::migrations::{self, MigrationError, SCHEMA_VERSION};
+use lunar::model::*;
+use lunar::saves::{self, Saves, Storage};
+use serde_json::{json, Value};
+
+fn fixture(version: u32) -> Value {
+    let path = format!(
+        "{}/tests/fixtures/saves/v{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        version
+    );
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn every_older_schema_upgrades_to_a_valid_game() {
+    for version in 0..SCHEMA_VERSION {
+        let save = fixture(version);
+        let written = save.get("schema_version").and_then(Value::as_u64);
+        assert_eq!(written.unwrap_or(0), u64::from(version));
+
+        let model = match migrations::migrate(save.clone()) {
+            Ok(model) => model,
+            Err(e) => panic!("v{} does not upgrade: {}", version, e),
+        };
+        assert_eq!(model.schema_version, SCHEMA_VERSION);
+        if let Err(violation) = invariants::check(&model) {
+            panic!("v{} upgrades to a broken game: {}", version, violation);
+        }
+        assert_eq!(model.player.name, "Anne Bonny");
+        assert_eq!(model.player.coins, 1234);
+        assert!(model.player.loan.is_none());
+        for port in model.ports.values() {
+            for kind in CARGO_KINDS.iter() {
+                assert!(port.cargos.iter().any(|c| c.kind == *kind));
+            }
+        }
+
+        // Loading a save goes through the same chain.
+        let loaded = serde_json::from_value::<Model>(save).unwrap();
+        assert_eq!(loaded.schema_version, SCHEMA_VERSION);
+    }
+}
+
+#[test]
+fn older_cargo_fields_keep_their_units() {
+    let mut save = fixture(1);
+    save["player"]["ship"]["cargos"]["food"]["unit"] = json!(7);
+    save["player"]["ship"]["cargos"]["sugar"]["unit"] = json!(3);
+
+    let model = migrations::migrate(save).unwrap();
+    let cargos = &model.player.ship.cargos;
+    assert_eq!(cargos.unit(CargoKind::Food), 7);
+    assert_eq!(cargos.unit(CargoKind::Sugar), 3);
+    assert_eq!(cargos.unit(CargoKind::Wood), 0);
+}
+
+#[test]
+fn older_skirmishes_are_tidied_up() {
+    let mut save = fixture(3);
+    save["current_screen"] = json!("SkirmishLoot");
+    save["player"]["ship"]["hull"] = json!(-4);
+    // Looting used to take cargo the enemy didn't have.
+    save["player"]["ship"]["cargos"]["Food"]["unit"] = json!(-2);
+
+    let model = migrations::migrate(save).unwrap();
+    assert_eq!(model.current_screen, Screen::MainNavigation);
+    assert_eq!(model.player.ship.hull, 0);
+    assert_eq!(model.player.ship.cargos.unit(CargoKind::Food), 0);
+    assert!(invariants::check(&model).is_ok());
+}
+
+#[test]
+fn older_log_entries_keep_their_text() {
+    let save = fixture(8);
+    let written = save["log"][0]["text"].as_str().unwrap().to_string();
+
+    let model = migrations::migrate(save).unwrap();
+    let text = &model.log[0].text;
+    assert_eq!(*text, Text::notice(written.clone()));
+    assert_eq!(i18n::render(Locale::French, text), written);
+}
+
+#[test]
+fn older_questions_keep_their_text() {
+    let save = fixture(9);
+    let written = save["pending_confirmation"]["question"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let model = migrations::migrate(save).unwrap();
+    let confirmation = model.pending_confirmation.unwrap();
+    assert_eq!(confirmation.question, Text::notice(written));
+    assert_eq!(*confirmation.msg, Msg::BuyAndReplaceShip(ShipClass::Brig));
+}
+
+#[test]
+fn saves_that_cannot_be_upgraded_are_refused() {
+    assert_eq!(
+        migrations::migrate(json!([1, 2])).err(),
+        Some(MigrationError::NotAnObject)
+    );
+
+    let mut save = fixture(SCHEMA_VERSION - 1);
+    save["schema_version"] = json!(SCHEMA_VERSION + 1);
+    assert_eq!(
+        migrations::migrate(save).err(),
+        Some(MigrationError::UnknownVersion(SCHEMA_VERSION + 1))
+    );
+
+    let mut save = fixture(4);
+    save["ports"] = json!("Nassau");
+    assert!(matches!(
+        migrations::migrate(save),
+        Err(MigrationError::Failed { from: 4, .. })
+    ));
+}
+
+#[test]
+fn older_journal_messages_are_upgraded() {
+    let upgraded = |mut msg: Value| {
+        migrations::upgrade_msg(&mut msg);
+        serde_json::from_value::<Msg>(msg).unwrap()
+    };
+
+    // Before version 2 the whole cargo was sent, and a single unit traded.
+    let cargo = json!({ "price": 30, "unit": 250, "kind": "Sugar" });
+    assert_eq!(
+        upgraded(json!({ "BuyCargo": ["Nassau", cargo] })),
+        Msg::BuyCargo(PortLocation::Nassau, CargoKind::Sugar, 1)
+    );
+    assert_eq!(
+        upgraded(json!({ "SellCargo": ["Barbados", "Wood"] })),
+        Msg::SellCargo(PortLocation::Barbados, CargoKind::Wood, 1)
+    );
+    let current = Msg::BuyCargo(PortLocation::PortRoyal, CargoKind::Food, 12);
+    assert_eq!(upgraded(serde_json::to_value(&current).unwrap()), current);
+    assert_eq!(upgraded(json!("DismissToasts")), Msg::DismissToasts);
+
+    // Before version 10 notices and questions were English text.
+    let notice = |text: &str| Text::notice(String::from(text));
+    assert_eq!(
+        upgraded(json!({ "Notify": ["Warning", "Cannot parse an age"] })),
+        Msg::Notify(Severity::Warning, notice("Cannot parse an age"))
+    );
+    assert_eq!(
+        upgraded(json!({ "AskConfirmation": ["Sure?", { "SellCargo": ["Nassau", "Wood"] }] })),
+        Msg::AskConfirmation(
+            notice("Sure?"),
+            Box::new(Msg::SellCargo(PortLocation::Nassau, CargoKind::Wood, 1))
+        )
+    );
+    let current = Msg::Notify(Severity::Info, Text::new("profile.journal_matches"));
+    assert_eq!(upgraded(serde_json::to_value(&current).unwrap()), current);
+
+    let entry = json!({
+        "msg": { "BuyCargo": ["Nassau", cargo] },
+        "date": "1680-01-01",
+        "screen": "DockMarket",
+    });
+    let entry = serde_json::from_value::<JournalEntry>(entry).unwrap();
+    assert_eq!(
+        entry.msg,
+        Msg::BuyCargo(PortLocation::Nassau, CargoKind::Sugar, 1)
+    );
+    assert!(entry.digest.is_none());
+}
+
+#[derive(Default)]
+struct Memory(BTreeMap<String, String>);
+
+impl Storage for Memory {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        self.0.insert(key.to_string(), value.to_string());
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.0.remove(key);
+    }
+}
+
+#[test]
+fn unmigratable_saves_are_reported_and_never_reset() {
+    let mut storage = Memory::default();
+    let mut saves = Saves::default();
+    let journal = lunar::journal::Journal::new(1);
+    let now = saves::now();
+    let kept = saves.create(
+        &mut storage,
+        String::from("Kept"),
+        &Model::default(),
+        &journal,
+        now,
+    );
+    let broken = saves.create(
+        &mut storage,
+        String::from("Future"),
+        &Model::default(),
+        &journal,
+        now,
+    );
+
+    let mut future = fixture(SCHEMA_VERSION - 1);
+    future["schema_version"] = json!(SCHEMA_VERSION + 1);
+    let raw = future.to_string();
+    storage.set(&saves::model_key(broken), &raw);
+
+    let (mut loaded, reported) = Saves::load(&mut storage);
+    assert_eq!(reported.len(), 1);
+    assert_eq!(reported[0].label, "Save slot Future");
+    assert_eq!(reported[0].raw, raw);
+    assert!(reported[0].error.contains("schema version"));
+    assert!(loaded.get(broken).is_none());
+    assert!(loaded.active.is_none());
+
+    // Playing on in another slot leaves the broken one as it was.
+    loaded.activate(&mut storage, Some(kept));
+    loaded.save_active(&mut storage, &Model::default(), &journal, now);
+    loaded.create(
+        &mut storage,
+        String::from("New"),
+        &Model::default(),
+        &journal,
+        now,
+    );
+    assert_eq!(storage.get(&saves::model_key(broken)), Some(raw));
+    let (_, reported) = Saves::load(&mut storage);
+    assert_eq!(reported.len(), 1);
+}
+
+#[test]
+fn slots_that_break_an_invariant_are_reported() {
+    let mut storage = Memory::default();
+    let mut saves = Saves::default();
+    let journal = lunar::journal::Journal::new(1);
+    let id = saves.create(
+        &mut storage,
+        String::from("Indebted"),
+        &Model::default(),
+        &journal,
+        saves::now(),
+    );
+
+    let mut save = fixture(SCHEMA_VERSION - 1);
+    save["player"]["coins"] = json!(-5);
+    let raw = save.to_string();
+    storage.set(&saves::model_key(id), &raw);
+
+    let (loaded, reported) = Saves::load(&mut storage);
+    assert!(loaded.get(id).is_none());
+    assert_eq!(reported.len(), 1);
+    assert_eq!(reported[0].raw, raw);
+    assert!(reported[0].error.contains("coins"));
+    assert!(saves::read_model(&raw).is_err());
+}
@@ -0,0 +1,65 @@
+//! Every screen has a hash route of its own, and following a link the game is
+//! not in a state for puts the route of the current screen back.
+
+use lunar::engine;
+use lunar::model::*;
+use lunar::routing::{self, route_of, screen_of, ROUTES};
+
+fn at(screen: Screen) -> Model {
+    let mut model = Model::default();
+    model.reseed(16);
+    model.current_screen = screen;
+    model
+}
+
+// Follows `hash` like the browser would, through the engine.
+fn follow(model: &mut Model, hash: &str) -> Option<&'static str> {
+    let current = model.current_screen.clone();
+    routing::follow(&current, hash, |msg| {
+        let _ = engine::apply(model, msg);
+        model.current_screen.clone()
+    })
+}
+
+#[test]
+fn routes_and_screens_map_both_ways() {
+    for (route, screen) in ROUTES.iter() {
+        assert_eq!(route_of(screen), *route);
+        assert_eq!(screen_of(route).as_ref(), Some(screen));
+    }
+    for (i, (route, _)) in ROUTES.iter().enumerate() {
+        assert!(ROUTES[i + 1..].iter().all(|(r, _)| r != route));
+    }
+    assert_eq!(screen_of("#/no-such-screen"), None);
+    assert_eq!(screen_of(""), None);
+}
+
+#[test]
+fn links_move_between_screens_the_game_allows() {
+    let mut model = at(Screen::MainNavigation);
+    assert_eq!(follow(&mut model, "#/dock"), None);
+    assert_eq!(model.current_screen, Screen::Dock);
+    assert_eq!(follow(&mut model, "#/dock/market"), None);
+    assert_eq!(model.current_screen, Screen::DockMarket);
+
+    // Reloading the page follows the route of the screen already shown.
+    let before = model.log.len();
+    assert_eq!(follow(&mut model, "#/dock/market"), None);
+    assert_eq!(model.log.len(), before);
+}
+
+#[test]
+fn illegal_routes_are_redirected_to_the_current_screen() {
+    let mut model = at(Screen::MainNavigation);
+    assert_eq!(follow(&mut model, "#/skirmish/loot"), Some("#/navigation"));
+    assert_eq!(model.current_screen, Screen::MainNavigation);
+    assert!(model.enemy.is_none());
+
+    // Some screens are never reached by a link, nor are unknown routes.
+    for hash in ["#/new-character", "#/skirmish", "#/no-such-screen"] {
+        let before = model.log.len();
+        assert_eq!(follow(&mut model, hash), Some("#/navigation"));
+        assert_eq!(model.current_screen, Screen::MainNavigation);
+        assert_eq!(model.log.len(), before);
+    }
+}
@@ -0,0 +1,172 @@
+//! Save slots are kept under keys of their own, so saving after an action
+//! only writes the slot being played, and they all load back as they were.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use lunar::journal::Journal;
+use lunar::model::*;
+use lunar::saves::{self, Saves, Storage};
+
+// Remembers which keys were written, to tell what a save touched.
+#[derive(Default)]
+struct Memory {
+    items: BTreeMap<String, String>,
+    written: Vec<String>,
+}
+
+impl Storage for Memory {
+    fn get(&self, key: &str) -> Option<String> {
+        self.items.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        self.written.push(key.to_string());
+        self.items.insert(key.to_string(), value.to_string());
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.items.remove(key);
+    }
+}
+
+fn now() -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(2022, 8, 17)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+}
+
+fn captain(name: &str) -> Model {
+    let mut model = Model::default();
+    model.player.name = name.to_string();
+    model
+}
+
+// Three slots, the last one created being the active one.
+fn three_slots(storage: &mut Memory) -> Saves {
+    let mut saves = Saves::default();
+    for name in ["Anne", "Mary", "Jack"] {
+        saves.create(
+            storage,
+            format!("{}'s voyage", name),
+            &captain(name),
+            &Journal::new(1),
+            now(),
+        );
+    }
+    saves
+}
+
+#[test]
+fn saving_an_action_only_writes_the_active_slot() {
+    let mut storage = Memory::default();
+    let mut saves = three_slots(&mut storage);
+    let active = saves.active.unwrap();
+    storage.written.clear();
+
+    let mut model = captain("Jack");
+    model.player.coins = 1;
+    saves.save_active(&mut storage, &model, &Journal::new(1), now());
+
+    assert_eq!(
+        storage.written,
+        [
+            saves::model_key(active),
+            saves::journal_key(Some(active)),
+            String::from(saves::INDEX_KEY),
+        ]
+    );
+    let (loaded, broken) = Saves::load(&mut storage);
+    assert!(broken.is_empty());
+    assert_eq!(loaded.active_slot().unwrap().coins(), 1);
+}
+
+#[test]
+fn slots_load_back_as_they_were_saved() {
+    let mut storage = Memory::default();
+    let mut saves = three_slots(&mut storage);
+    let first = saves.sorted().last().unwrap().id;
+    let copy = saves.duplicate(&mut storage, first, now()).unwrap();
+    saves.rename(&mut storage, copy, String::from("Spare"));
+    let last = saves.active.unwrap();
+    saves.delete(&mut storage, last);
+
+    let (loaded, broken) = Saves::load(&mut storage);
+    assert!(broken.is_empty());
+    assert!(loaded == saves);
+    assert!(loaded.active.is_none());
+    assert_eq!(loaded.get(copy).unwrap().name, "Spare");
+    assert!(storage.get(&saves::model_key(last)).is_none());
+    assert!(storage.get(&saves::journal_key(Some(last))).is_none());
+}
+
+#[test]
+fn a_game_without_a_slot_keeps_its_journal_apart() {
+    let mut storage = Memory::default();
+    let mut saves = Saves::default();
+    let mut journal = Journal::new(3);
+    journal.record(Msg::SetPlayerAge(30), &Model::default());
+
+    saves.save_active(&mut storage, &Model::default(), &journal, now());
+    assert_eq!(storage.written, [saves::journal_key(None)]);
+    assert!(saves.current_journal(&storage, &Model::default()) == journal);
+
+    saves.create(
+        &mut storage,
+        String::from("New"),
+        &Model::default(),
+        &journal,
+        now(),
+    );
+    assert!(storage.get(&saves::journal_key(None)).is_none());
+    assert!(saves.current_journal(&storage, &Model::default()) == journal);
+}
+
+#[test]
+fn slots_kept_under_the_legacy_key_are_moved_to_their_own() {
+    let mut storage = Memory::default();
+    let saves = three_slots(&mut Memory::default());
+    storage.set(saves::LEGACY_KEY, &serde_json::to_string(&saves).unwrap());
+
+    let (loaded, broken) = Saves::load(&mut storage);
+    assert!(broken.is_empty());
+    assert!(loaded == saves);
+    assert!(storage.get(saves::LEGACY_KEY).is_none());
+    for slot in saves.slots.iter() {
+        assert!(storage.get(&saves::model_key(slot.id)).is_some());
+    }
+}
+
+#[test]
+fn ui_only_messages_are_not_worth_saving() {
+    assert!(Msg::DismissToasts.is_ui_only());
+    assert!(Msg::Notify(Severity::Info, Text::notice(String::new())).is_ui_only());
+    assert!(Msg::CancelConfirmation.is_ui_only());
+    assert!(!Msg::Confirm.is_ui_only());
+    assert!(!Msg::SwitchScreen(Screen::MainNavigation).is_ui_only());
+}
+
+#[test]
+fn slots_are_only_deleted_once_confirmed() {
+    let mut storage = Memory::default();
+    let mut saves = three_slots(&mut storage);
+    let id = saves.active.unwrap();
+
+    saves.ask_deletion(id);
+    assert_eq!(saves.pending_deletion, Some(id));
+    saves.cancel_deletion();
+    assert!(saves.pending_deletion.is_none());
+    assert!(saves.get(id).is_some());
+
+    // Slots that are gone can't be asked about.
+    saves.ask_deletion(id + 100);
+    saves.confirm_deletion(&mut storage);
+    assert_eq!(saves.slots.len(), 3);
+
+    saves.ask_deletion(id);
+    saves.confirm_deletion(&mut storage);
+    assert!(saves.pending_deletion.is_none());
+    assert!(saves.get(id).is_none());
+    assert!(storage.get(&saves::model_key(id)).is_none());
+}
@@ -0,0 +1,53 @@
+//! The balance simulation has to be reproducible to be worth tuning against,
+//! and its numbers have to describe games that could actually be played.
+
+use lunar::simulation::{simulate, STRATEGIES};
+
+const GAMES: usize = 20;
+const MAX_ACTIONS: u32 = 300;
+// A single voyage takes at most this many days.
+const LONGEST_VOYAGE: i64 = 9;
+
+#[test]
+fn the_same_seed_gives_the_same_report() {
+    for (name, new_strategy) in STRATEGIES {
+        let first = simulate(name, new_strategy, GAMES, 42, MAX_ACTIONS);
+        let second = simulate(name, new_strategy, GAMES, 42, MAX_ACTIONS);
+        assert!(first.results == second.results, "{} diverged", name);
+        assert_eq!(first.to_string(), second.to_string());
+    }
+}
+
+#[test]
+fn reports_describe_playable_games() {
+    for (name, new_strategy) in STRATEGIES {
+        let report = simulate(name, new_strategy, GAMES, 7, MAX_ACTIONS);
+        assert_eq!(report.results.len(), GAMES);
+
+        for result in &report.results {
+            assert!(
+                result.coins >= 0,
+                "{} ended with {} coins",
+                name,
+                result.coins
+            );
+            assert!(
+                result.checkpoint_coins.iter().all(|&c| c >= 0),
+                "{} went below zero coins at a checkpoint",
+                name
+            );
+            assert!(
+                (0..=LONGEST_VOYAGE * MAX_ACTIONS as i64).contains(&result.days),
+                "{} lasted {} days in {} actions",
+                name,
+                result.days,
+                MAX_ACTIONS
+            );
+            assert!(result.refused_actions <= MAX_ACTIONS);
+        }
+
+        let days = report.days_survived().unwrap();
+        assert!(days.min >= 0 && days.max <= LONGEST_VOYAGE * MAX_ACTIONS as i64);
+        assert!(report.final_coins().unwrap().min >= 0);
+    }
+}
@@ -0,0 +1,111 @@
+//! Ports charge docking fees and tariffs by how the player's nation stands
+//! with theirs, and nothing in tariffs on the black market.
+
+use lunar::engine;
+use lunar::market::{self, Venue};
+use lunar::model::*;
+use lunar::tariffs::{self, Relation};
+use strum::IntoEnumIterator;
+
+fn docked_at(l: PortLocation, nationality: Nationality) -> Model {
+    let mut model = Model {
+        current_screen: Screen::DockMarket,
+        current_port_location: l,
+        ..Default::default()
+    };
+    model.player.nationality = nationality;
+    model.player.coins = 100_000;
+    model
+}
+
+#[test]
+fn relations_are_symmetric_and_home_is_home() {
+    for a in Nationality::iter() {
+        assert_eq!(tariffs::relation(&a, &a), Relation::Home);
+        for b in Nationality::iter() {
+            assert_eq!(tariffs::relation(&a, &b), tariffs::relation(&b, &a));
+        }
+    }
+}
+
+#[test]
+fn worse_relations_cost_more() {
+    for port in CONTENT.ports.values() {
+        let mut by_relation: Vec<(Relation, tariffs::Levies)> = Nationality::iter()
+            .map(|nationality| {
+                let player = Player {
+                    nationality,
+                    ..Default::default()
+                };
+                (
+                    tariffs::relation(&player.nationality, &port.nationality),
+                    tariffs::levies(port, &player),
+                )
+            })
+            .collect();
+        by_relation.sort_by_key(|(relation, _)| *relation as i32);
+
+        for pair in by_relation.windows(2) {
+            let ((_, better), (_, worse)) = (pair[0], pair[1]);
+            assert!(better.docking_fee <= worse.docking_fee);
+            assert!(better.import_percent <= worse.import_percent);
+            assert!(better.export_percent <= worse.export_percent);
+        }
+    }
+}
+
+#[test]
+fn docking_fee_is_charged_on_arrival_as_far_as_coins_go() {
+    let to = PortLocation::PortRoyal;
+    let from = PORT_LOCATIONS.iter().copied().find(|&l| l != to).unwrap();
+    for coins in [100_000, 1, 0] {
+        let mut model = docked_at(from, Nationality::Spanish);
+        model.current_screen = Screen::MainNavigation;
+        model.player.coins = coins;
+        model.player.ship.cargos.get_mut(CargoKind::Food).unit = 20;
+        let fee = tariffs::levies(&model.ports[&to], &model.player).docking_fee;
+
+        assert_eq!(
+            engine::apply(&mut model, Msg::SwitchPlayerLocation(to)),
+            Ok(())
+        );
+        assert_eq!(model.player.coins, coins - fee.min(coins));
+    }
+}
+
+#[test]
+fn trades_pay_tariffs_on_the_market_only() {
+    let l = PortLocation::PortRoyal;
+    let kind = CargoKind::Sugar;
+    let mut model = docked_at(l, Nationality::Spanish);
+    let levies = tariffs::levies(&model.ports[&l], &model.player);
+    assert!(levies.import_percent > 0 && levies.export_percent > 0);
+
+    let cost = market::buy_cost(&model, l, kind, Venue::Market, 10);
+    let coins = model.player.coins;
+    assert_eq!(
+        engine::apply(&mut model, Msg::BuyCargo(l, kind, 10)),
+        Ok(())
+    );
+    assert_eq!(
+        model.player.coins,
+        coins - cost - tariffs::duty(cost, levies.export_percent)
+    );
+
+    let proceeds = market::sell_proceeds(&model, l, kind, Venue::Market, 10);
+    let coins = model.player.coins;
+    assert_eq!(
+        engine::apply(&mut model, Msg::SellCargo(l, kind, 10)),
+        Ok(())
+    );
+    assert_eq!(
+        model.player.coins,
+        coins + proceeds - tariffs::duty(proceeds, levies.import_percent)
+    );
+
+    assert_eq!(tariffs::export_duty(&model, l, Venue::BlackMarket, cost), 0);
+    assert_eq!(
+        tariffs::import_duty(&model, l, Venue::BlackMarket, proceeds),
+        0
+    );
+}
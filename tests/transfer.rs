@@ -0,0 +1,111 @@
+//! A game exported as JSON or as a save code imports back unchanged, and an
+//! import that breaks a rule of the game is refused.
+
+use lunar::engine;
+use lunar::model::*;
+use lunar::transfer::{self, ImportError};
+use serde_json::{json, Value};
+
+// A game some way in, so there is more than the defaults to carry over.
+fn played() -> Model {
+    let mut model = Model::default();
+    model.reseed(11);
+    model.current_screen = Screen::MainNavigation;
+    model.player.ship.cargos.get_mut(CargoKind::Food).unit = 30;
+    let to = PORT_LOCATIONS
+        .iter()
+        .copied()
+        .find(|&l| l != model.current_port_location)
+        .unwrap();
+    engine::apply(&mut model, Msg::SwitchPlayerLocation(to)).unwrap();
+    engine::apply(&mut model, Msg::BuyCargo(to, CargoKind::Sugar, 5)).unwrap();
+    model
+}
+
+fn import_json(value: Value) -> Result<Model, ImportError> {
+    transfer::import(&value.to_string())
+}
+
+fn exported() -> Value {
+    serde_json::from_str(&transfer::export_json(&played())).unwrap()
+}
+
+#[test]
+fn json_exports_import_unchanged() {
+    let model = played();
+    let imported = transfer::import(&transfer::export_json(&model)).unwrap();
+    assert!(imported == model);
+}
+
+#[test]
+fn save_codes_import_unchanged() {
+    let model = played();
+    let code = transfer::export_code(&model);
+    assert!(code
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+    // Pasted codes often pick up surrounding whitespace.
+    let imported = transfer::import(&format!("  {}\n", code)).unwrap();
+    assert!(imported == model);
+}
+
+#[test]
+fn codes_that_are_not_base64_are_refused() {
+    assert!(matches!(
+        transfer::import("not a save code!"),
+        Err(ImportError::Decode(_))
+    ));
+    assert!(matches!(
+        transfer::import(&base64::encode_config("[1, 2]", base64::URL_SAFE_NO_PAD)),
+        Err(ImportError::Parse(_))
+    ));
+}
+
+#[test]
+fn cargo_beyond_the_hold_is_refused() {
+    let mut save = exported();
+    let capacity = save["player"]["ship"]["cargos_capacity"].as_i64().unwrap();
+    save["player"]["ship"]["cargos"]["Wood"] = json!({
+        "price": 0,
+        "unit": capacity + 1,
+        "kind": "Wood",
+    });
+
+    match import_json(save) {
+        Err(ImportError::Invalid(reason)) => assert!(reason.contains("space for")),
+        _ => panic!("cargo beyond the hold was imported"),
+    }
+}
+
+#[test]
+fn hull_beyond_its_capacity_is_refused() {
+    let mut save = exported();
+    let capacity = save["player"]["ship"]["hull_capacity"].as_i64().unwrap();
+    save["player"]["ship"]["hull"] = json!(capacity + 1);
+
+    match import_json(save) {
+        Err(ImportError::Invalid(reason)) => assert!(reason.contains("hull")),
+        _ => panic!("a hull beyond its capacity was imported"),
+    }
+}
+
+#[test]
+fn unknown_port_locations_are_refused() {
+    let mut save = exported();
+    save["current_port_location"] = json!("Atlantis");
+    assert!(matches!(import_json(save), Err(ImportError::Parse(_))));
+
+    let mut save = exported();
+    let port = save["ports"]["Nassau"].take();
+    save["ports"].as_object_mut().unwrap().remove("Nassau");
+    save["ports"]["Atlantis"] = port;
+    assert!(matches!(import_json(save), Err(ImportError::Parse(_))));
+
+    let mut save = exported();
+    save["ports"].as_object_mut().unwrap().remove("Nassau");
+    match import_json(save) {
+        Err(ImportError::Invalid(reason)) => assert!(reason.contains("Nassau")),
+        _ => panic!("a game missing a port was imported"),
+    }
+}
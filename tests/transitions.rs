@@ -0,0 +1,102 @@
+//! Walks every edge of `transitions::TRANSITIONS`, and checks that every
+//! screen pair missing from it is refused.
+
+use lunar::engine::{self, GameError};
+use lunar::model::*;
+use lunar::transitions::{guard_of, Guard, SCREENS, TRANSITIONS};
+
+fn black_market() -> PortLocation {
+    let ports = Model::default().ports;
+    *PORT_LOCATIONS
+        .iter()
+        .find(|l| ports[l].black_market)
+        .unwrap()
+}
+
+// A game on `screen`, docked at a port with a black market, and with an enemy
+// whenever the screen needs one.
+fn model_on(screen: &Screen, enemy_crew: i32) -> Model {
+    let mut model = Model {
+        current_screen: screen.clone(),
+        current_port_location: black_market(),
+        ..Default::default()
+    };
+    if screen.is_skirmish() {
+        let mut enemy = Enemy {
+            ship: CONTENT.ships[&ShipClass::Sloop].clone(),
+            ..Default::default()
+        };
+        enemy.ship.crew = enemy_crew;
+        model.enemy = Some(enemy);
+    }
+    model
+}
+
+#[test]
+fn every_edge_is_taken_when_its_guard_holds() {
+    for (from, to, _) in TRANSITIONS.iter() {
+        let mut model = model_on(from, 0);
+
+        assert_eq!(
+            engine::apply(&mut model, Msg::SwitchScreen(to.clone())),
+            Ok(()),
+            "{:?} -> {:?}",
+            from,
+            to
+        );
+        assert_eq!(&model.current_screen, to);
+    }
+}
+
+#[test]
+fn guarded_edges_are_refused_when_the_enemy_still_fights() {
+    let guarded = TRANSITIONS
+        .iter()
+        .filter(|(_, _, guard)| *guard == Guard::EnemyDefeated);
+
+    for (from, to, _) in guarded {
+        let mut model = model_on(from, MINIMUM_SHIP_CREW.into());
+
+        assert_eq!(
+            engine::apply(&mut model, Msg::SwitchScreen(to.clone())),
+            Err(GameError::EnemyNotDefeated),
+            "{:?} -> {:?}",
+            from,
+            to
+        );
+        assert_eq!(&model.current_screen, from);
+    }
+}
+
+#[test]
+fn black_markets_are_refused_at_other_ports() {
+    let ports = Model::default().ports;
+    for l in PORT_LOCATIONS.iter().filter(|l| !ports[l].black_market) {
+        let mut model = model_on(&Screen::Dock, 0);
+        model.current_port_location = *l;
+
+        assert_eq!(
+            engine::apply(&mut model, Msg::SwitchScreen(Screen::DockBlackMarket)),
+            Err(GameError::NoBlackMarket(*l))
+        );
+        assert_eq!(model.current_screen, Screen::Dock);
+    }
+}
+
+#[test]
+fn screens_without_an_edge_are_refused() {
+    for from in SCREENS.iter() {
+        for to in SCREENS.iter().filter(|to| guard_of(from, to).is_none()) {
+            let mut model = model_on(from, 0);
+
+            assert_eq!(
+                engine::apply(&mut model, Msg::SwitchScreen(to.clone())),
+                Err(GameError::ScreenUnreachable {
+                    from: from.clone(),
+                    to: to.clone()
+                })
+            );
+            assert_eq!(&model.current_screen, from);
+        }
+    }
+}